@@ -0,0 +1,87 @@
+use std::fmt;
+
+use bombardment_strategy::BombardmentStrategy;
+use layout_history::LayoutHistory;
+use placement_strategy::PlacementStrategy;
+
+/// How hard the computer opponent plays.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "lowercase"))]
+pub enum AiLevel {
+    Beginner,
+    Intermediate,
+    Advanced,
+    /// Cheats by looking at the opponent's planes, for testing.
+    Hack,
+    /// Plays like the level matching the skill the opponent shows in its
+    /// layout, see `PlacementAnalysis`, and like an intermediate until the
+    /// opponent has placed all its planes.
+    Auto,
+    /// Remembers where the opponent put its planes in past games and hunts
+    /// there first, see `AiPlayer::new_random_with_stats`.
+    Adaptive,
+}
+
+impl AiLevel {
+    pub fn new(from: &str) -> Option<AiLevel> {
+        use AiLevel::*;
+        match from {
+            "beginner" => Some(Beginner),
+            "intermediate" => Some(Intermediate),
+            "advanced" => Some(Advanced),
+            "hack" => Some(Hack),
+            "auto" => Some(Auto),
+            "adaptive" => Some(Adaptive),
+            _ => None,
+        }
+    }
+    pub fn bombardment_strategy(&self) -> BombardmentStrategy {
+        use AiLevel::*;
+        match self {
+            &Beginner => BombardmentStrategy::Random,
+            &Intermediate => BombardmentStrategy::HuntAndTarget,
+            &Advanced => BombardmentStrategy::Density,
+            &Hack => BombardmentStrategy::Cheat,
+            &Auto => Intermediate.bombardment_strategy(),
+            &Adaptive => BombardmentStrategy::Adaptive(LayoutHistory::default()),
+        }
+    }
+    /// Ranked by how long the other levels take to shoot down the layouts in
+    /// `battleplanes-arena simulate`: packed planes go down fastest, spread
+    /// out ones last.
+    pub fn placement_strategy(&self) -> PlacementStrategy {
+        use AiLevel::*;
+        match self {
+            &Beginner => PlacementStrategy::ClusteredDecoy,
+            &Intermediate => PlacementStrategy::Random,
+            &Advanced => PlacementStrategy::SpreadOut,
+            &Hack => PlacementStrategy::Random,
+            &Auto => Intermediate.placement_strategy(),
+            &Adaptive => Advanced.placement_strategy(),
+        }
+    }
+}
+
+impl Default for AiLevel {
+    fn default() -> AiLevel {
+        AiLevel::Intermediate
+    }
+}
+
+impl fmt::Display for AiLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AiLevel::*;
+        write!(f, "{}", match self {
+            &Beginner => "beginner",
+            &Intermediate => "intermediate",
+            &Advanced => "advanced",
+            &Hack => "hack",
+            &Auto => "auto",
+            &Adaptive => "adaptive",
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use rand;
+use rand::{Rng, XorShiftRng};
+
+use player::Player;
+use game::Game;
+use board::Board;
+use board_spec::BoardSpec;
+use rules::{Rules, DEFAULT_FLEET_SIZE};
+use ai_level::AiLevel;
+use bombardment_strategy::BombardmentStrategy;
+use layout_history::LayoutHistory;
+use placement_analysis::PlacementAnalysis;
+use placement_strategy::PlacementStrategy;
+use player_stats::PlayerStats;
+use coordinate::Coordinate;
+use orientation::Orientation;
+
+/// The computer opponent: it sends its planes to mission following a layout
+/// generated up front, and bombards according to its level. At the `Auto`
+/// level, it settles on a level once the opponent has placed its planes,
+/// weighing their layout against their past ones; at the `Adaptive` level, it hunts where the opponent put its planes in past
+/// games.
+///
+/// It draws on its own random generator, so an AI built by
+/// `new_random_with_rng` from a seeded generator always plays the same
+/// layout and the same shots against the same moves.
+#[derive(Clone)]
+pub struct AiPlayer {
+    layout: Board,
+    level: AiLevel,
+    /// The past layouts of the opponent, only looked at by `Auto` and
+    /// `Adaptive` AIs.
+    history: LayoutHistory,
+    /// How it bombards when not the way of its level, see
+    /// `new_with_bombardment_strategy`.
+    bombardment: Option<BombardmentStrategy>,
+    rng: XorShiftRng,
+}
+
+impl AiPlayer {
+    pub fn new(layout: Board) -> AiPlayer {
+        AiPlayer::new_with_level(layout, AiLevel::default())
+    }
+    pub fn new_with_level(layout: Board, level: AiLevel) -> AiPlayer {
+        AiPlayer::new_with_history(layout, level, LayoutHistory::default())
+    }
+    pub fn new_with_history(layout: Board, level: AiLevel, history: LayoutHistory) -> AiPlayer {
+        AiPlayer::new_with_rng(layout, level, history, rand::thread_rng().gen())
+    }
+    fn new_with_rng(layout: Board, level: AiLevel, history: LayoutHistory, rng: XorShiftRng) -> AiPlayer {
+        AiPlayer {
+            layout: layout,
+            level: level,
+            history: history,
+            bombardment: None,
+            rng: rng,
+        }
+    }
+    /// An AI bombarding by `bombardment` whatever its level, as the
+    /// contenders of a `Simulation` do.
+    pub fn new_with_bombardment_strategy<R: Rng>(layout: Board, bombardment: BombardmentStrategy, rng: &mut R) -> AiPlayer {
+        AiPlayer {
+            bombardment: Some(bombardment),
+            ..AiPlayer::new_with_rng(layout, AiLevel::default(), LayoutHistory::default(), rng.gen())
+        }
+    }
+    pub fn new_random() -> AiPlayer {
+        AiPlayer::new_random_with_level(AiLevel::default())
+    }
+    pub fn new_random_with_level(level: AiLevel) -> AiPlayer {
+        AiPlayer::new_random_with_rng(level, &mut rand::thread_rng())
+    }
+    pub fn new_random_with_rng<R: Rng>(level: AiLevel, rng: &mut R) -> AiPlayer {
+        AiPlayer::new_random_with_spec(level, &BoardSpec::default(), rng)
+    }
+    /// An AI whose layout fits on maps of the size `spec`.
+    pub fn new_random_with_spec<R: Rng>(level: AiLevel, spec: &BoardSpec, rng: &mut R) -> AiPlayer {
+        let layout = level.placement_strategy().new_layout_with_fleet(spec, DEFAULT_FLEET_SIZE, rng);
+        AiPlayer::new_with_rng(layout, level, LayoutHistory::default(), rng.gen())
+    }
+    /// An AI playing as `player` in a game played by `rules`.
+    pub fn new_random_with_rules<R: Rng>(level: AiLevel, rules: &Rules, player: usize, rng: &mut R) -> AiPlayer {
+        AiPlayer::new_random_with_stats(level, rules, player, &PlayerStats::new(), rng)
+    }
+    /// Same as `new_random_with_rules`, against an opponent the AI played
+    /// before: an `Adaptive` AI keeps its planes off the tiles the opponent
+    /// fired at, and hunts where the opponent put its planes.
+    pub fn new_random_with_stats<R: Rng>(level: AiLevel, rules: &Rules, player: usize, stats: &PlayerStats, rng: &mut R) -> AiPlayer {
+        let placement = match level {
+            AiLevel::Adaptive if stats.shots.total() > 0 => PlacementStrategy::AntiHeatmap(stats.shots.clone()),
+            _ => level.placement_strategy(),
+        };
+        let layout = placement.new_layout_with_mixed_fleet(&rules.spec, rules.fleets[player].clone(), rng);
+        AiPlayer::new_with_rng(layout, level, stats.layouts.clone(), rng.gen())
+    }
+    pub fn layout(&self) -> &Board {
+        &self.layout
+    }
+    pub fn level(&self) -> AiLevel {
+        self.level
+    }
+    pub fn history(&self) -> &LayoutHistory {
+        &self.history
+    }
+}
+
+impl Player for AiPlayer {
+    fn next_plane(&mut self, game: &Game, me: usize) -> Option<(Coordinate, Orientation)> {
+        let current_index = game.players[me].board.planes().len();
+        self.layout.planes().get(current_index).map(|plane| {
+            (plane.head().clone(), plane.orientation().clone())
+        })
+    }
+    fn next_target(&mut self, game: &Game, me: usize) -> Option<Coordinate> {
+        let opponent = match game.opponent_of(me) {
+            Some(opponent) => opponent,
+            None => return None,
+        };
+        let opponent_board = &game.players[opponent].board;
+        if self.bombardment.is_none() && self.level == AiLevel::Auto && opponent_board.is_initialized() {
+            self.level = PlacementAnalysis::new_with_history(opponent_board, &self.history).level();
+            debug!("the layout of player {} is one of the {} level", opponent, self.level);
+        }
+        let scrapbook = &game.players[me].scrapbook;
+        match (&self.bombardment, self.level) {
+            (&Some(ref bombardment), _) => bombardment.next_target_with_rng(scrapbook, opponent_board, &mut self.rng),
+            (&None, AiLevel::Adaptive) => BombardmentStrategy::next_adaptive_target_with_rng(&self.history, scrapbook, &mut self.rng),
+            (&None, level) => level.bombardment_strategy().next_target_with_rng(scrapbook, opponent_board, &mut self.rng),
+        }
+    }
+}
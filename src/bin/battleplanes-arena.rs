@@ -0,0 +1,608 @@
+//! Pits two bots against each other over a number of seeded games and
+//! reports their win rates.
+//!
+//! A bot is any executable which speaks this line-based protocol on its
+//! stdin and stdout. The arena sends:
+//!
+//! * `game PLAYER WIDTHxHEIGHT SEED` when a game starts, `PLAYER` being the
+//!   seat of the bot, 0 or 1
+//! * `shape NAME X,Y X,Y ...` for every shape of plane in the game, with the
+//!   offsets of its body from the head when flying north, `y` growing south
+//! * `fleet NAME NAME ...`, the planes of the bot in the order they are
+//!   placed, and `opponent NAME NAME ...`, those of the other bot
+//! * `place NAME`, to which the bot answers with a head and an orientation,
+//!   e.g. `E5N`
+//! * `fire`, to which the bot answers with a target, e.g. `B7`
+//! * `shot PLAYER TARGET RESULT` after every bombardment of either player,
+//!   `RESULT` being `hit`, `miss` or `kill NAME`
+//! * `gameover WINNER` when the game is over, after which stdin is closed
+//!
+//! A bot which does not answer within the timeout, exits, or answers with a
+//! move the rules reject forfeits the game.
+//!
+//! `battleplanes-arena --bot LEVEL` is itself a bot playing like the AI of
+//! that level, e.g. `battleplanes-arena "battleplanes-arena --bot advanced"
+//! "battleplanes-arena --bot beginner"`.
+//!
+//! `battleplanes-arena simulate CONTENDER CONTENDER ...` plays the built-in
+//! AIs against each other in-process instead, on several threads, every
+//! contender against every other one, and reports their win rates and how
+//! many shots they needed to win as text, CSV or JSON. Contenders are AI
+//! levels like `advanced`, or strategies like `density/random`.
+extern crate battleplanes;
+extern crate rand;
+extern crate serde_json;
+
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+struct Options {
+    simulate: bool,
+    games: Option<usize>,
+    seed: Option<usize>,
+    timeout: Duration,
+    threads: usize,
+    format: Format,
+    rules: battleplanes::Rules,
+    /// The bot commands, or the contenders of a simulation.
+    players: Vec<String>,
+}
+
+/// How the results of a simulation are printed.
+#[derive(PartialEq)]
+enum Format {
+    Text,
+    /// One line per side of every match-up.
+    Csv,
+    /// One line per number of shots to win of every side.
+    HistogramCsv,
+    Json,
+}
+
+fn parse_args() -> Options {
+    let mut options = Options {
+        simulate: false,
+        games: None,
+        seed: None,
+        timeout: Duration::from_millis(1000),
+        threads: battleplanes::DEFAULT_THREADS,
+        format: Format::Text,
+        rules: battleplanes::Rules::default(),
+        players: Vec::new(),
+    };
+    let mut spec = battleplanes::BoardSpec::default();
+    let mut raw_fleet = battleplanes::DEFAULT_FLEET_SIZE.to_string();
+    let mut shape = battleplanes::PlaneShape::default();
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("simulate") {
+        options.simulate = true;
+        args.next();
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => {
+                let raw_games = args.next().unwrap_or("".to_string());
+                match raw_games.parse::<usize>() {
+                    Ok(games) if games > 0 => options.games = Some(games),
+                    _ => usage(&format!("invalid number of games: {}", raw_games)),
+                }
+            },
+            "--seed" => {
+                let raw_seed = args.next().unwrap_or("".to_string());
+                match raw_seed.parse::<usize>() {
+                    Ok(seed) => options.seed = Some(seed),
+                    Err(_) => usage(&format!("invalid seed: {}", raw_seed)),
+                }
+            },
+            "--timeout" => {
+                let raw_timeout = args.next().unwrap_or("".to_string());
+                match raw_timeout.parse::<u64>() {
+                    Ok(millis) if millis > 0 => options.timeout = Duration::from_millis(millis),
+                    _ => usage(&format!("invalid timeout: {}", raw_timeout)),
+                }
+            },
+            "--threads" => {
+                let raw_threads = args.next().unwrap_or("".to_string());
+                match raw_threads.parse::<usize>() {
+                    Ok(threads) if threads > 0 => options.threads = threads,
+                    _ => usage(&format!("invalid number of threads: {}", raw_threads)),
+                }
+            },
+            "--format" => {
+                let raw_format = args.next().unwrap_or("".to_string());
+                options.format = match raw_format.as_str() {
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    "histogram-csv" => Format::HistogramCsv,
+                    "json" => Format::Json,
+                    _ => usage(&format!("unknown format: {}", raw_format)),
+                };
+            },
+            "--size" => {
+                let raw_spec = args.next().unwrap_or("".to_string());
+                match battleplanes::BoardSpec::new_from_str(raw_spec.as_str()) {
+                    Some(new_spec) => spec = new_spec,
+                    None => usage(&format!("invalid map size: {}, sides go from {} to {}", raw_spec, battleplanes::MIN_SIZE, battleplanes::MAX_SIZE)),
+                }
+            },
+            "--fleet" => {
+                match args.next() {
+                    Some(fleet) => raw_fleet = fleet,
+                    None => usage("--fleet needs a fleet, e.g. 3 or \"1 bomber, 2 fighters\""),
+                }
+            },
+            "--shape" => {
+                let raw_shape = args.next().unwrap_or("".to_string());
+                shape = match battleplanes::PlaneShape::new_named(raw_shape.as_str()) {
+                    Some(shape) => shape,
+                    None => match battleplanes::PlaneShape::load_from(&raw_shape) {
+                        Ok(shape) => shape,
+                        Err(error) => usage(&format!("cannot read the plane shape {}: {}", raw_shape, error)),
+                    },
+                };
+            },
+            _ if arg.starts_with("--") => usage(&format!("unknown argument: {}", arg)),
+            _ => options.players.push(arg),
+        }
+    }
+    if options.simulate && options.players.is_empty() {
+        usage("at least one contender is needed");
+    }
+    if !options.simulate && options.players.len() != 2 {
+        usage("two bots are needed");
+    }
+    if !options.simulate && options.players.iter().any(|bot| bot.trim().is_empty()) {
+        usage("a bot needs a command to run");
+    }
+    match battleplanes::Rules::new_from_fleet_str(spec, shape, raw_fleet.as_str()).filter(battleplanes::Rules::can_be_laid_out) {
+        Some(rules) => options.rules = rules,
+        None => usage(&format!("invalid fleet: {}", raw_fleet)),
+    }
+    options
+}
+
+fn usage(error: &str) -> ! {
+    println!("{}", error);
+    println!("usage: battleplanes-arena [--games NUMBER] [--seed NUMBER] [--timeout MILLISECONDS] [--size WIDTHxHEIGHT] [--fleet FLEET[:FLEET]] [--shape classic|fighter|bomber|FILE] BOT BOT");
+    println!("       battleplanes-arena simulate [--games NUMBER] [--seed NUMBER] [--threads NUMBER] [--format text|csv|histogram-csv|json] [--size WIDTHxHEIGHT] [--fleet FLEET[:FLEET]] [--shape classic|fighter|bomber|FILE] CONTENDER...");
+    println!("       battleplanes-arena --bot beginner|intermediate|advanced|hack|auto|adaptive");
+    println!("contenders are AI levels or BOMBARDMENT/PLACEMENT strategies, bombardments being random, hunt_and_target, density or cheat and placements random, edge_hugging, spread_out, anti_heatmap or clustered_decoy");
+    std::process::exit(1);
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_ref().map(String::as_str) == Some("--bot") {
+        let raw_level = args.next().unwrap_or("".to_string());
+        match battleplanes::AiLevel::new(raw_level.as_str()) {
+            Some(level) => run_bot(level),
+            None => usage(&format!("unknown AI level: {}", raw_level)),
+        }
+        return;
+    }
+    let options = parse_args();
+    if options.simulate {
+        simulate(&options);
+        return;
+    }
+    let games = options.games.unwrap_or(100);
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    println!("Seed: {}", seed);
+    let mut wins = vec![0; 2];
+    let mut forfeits = vec![0; 2];
+    for game_number in 0..games {
+        // The bots take turns at sitting in the first seat.
+        let seats = if game_number % 2 == 0 { vec![0, 1] } else { vec![1, 0] };
+        let game_seed = seed.wrapping_add(game_number);
+        match play(&options, &seats, game_seed) {
+            Ok(outcome) => {
+                let winner = seats[outcome.winner];
+                wins[winner] += 1;
+                match outcome.forfeit {
+                    Some(reason) => {
+                        forfeits[1 - winner] += 1;
+                        println!("Game {} (seed {}): bot {} wins, bot {} forfeits: {}", game_number + 1, game_seed, winner + 1, 2 - winner, reason);
+                    },
+                    None => println!("Game {} (seed {}): bot {} wins", game_number + 1, game_seed, winner + 1),
+                }
+            },
+            Err(error) => {
+                println!("Cannot start the bots: {}", error);
+                std::process::exit(1);
+            },
+        }
+    }
+    for bot in 0..2 {
+        let rate = 100.0 * wins[bot] as f64 / games as f64;
+        println!("Bot {} ({}): {} wins, {:.1}%, {} forfeits", bot + 1, options.players[bot], wins[bot], rate, forfeits[bot]);
+    }
+}
+
+/// Plays every contender against every other one, or against itself when
+/// there is only one.
+fn simulate(options: &Options) {
+    let mut contenders = Vec::new();
+    for raw_contender in &options.players {
+        match battleplanes::Contender::new_from_str(raw_contender) {
+            Some(contender) => contenders.push(contender),
+            None => usage(&format!("unknown contender: {}", raw_contender)),
+        }
+    }
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut simulation = battleplanes::Simulation::new(options.rules.clone(), options.games.unwrap_or(1000), seed)
+        .expect("the fleets read from the arguments are for two players");
+    simulation.threads = options.threads;
+    let mut match_ups = Vec::new();
+    for first in 0..contenders.len() {
+        for second in first + 1..contenders.len() {
+            match_ups.push(simulation.play(&contenders[first], &contenders[second]));
+        }
+    }
+    if contenders.len() == 1 {
+        match_ups.push(simulation.play(&contenders[0], &contenders[0]));
+    }
+    match options.format {
+        Format::Text => {
+            println!("Seed: {}", seed);
+            for match_up in &match_ups {
+                print_match_up(match_up);
+            }
+        },
+        Format::Csv => {
+            println!("first,second,games,side,contender,wins,win_rate,mean_shots_to_win,median_shots_to_win");
+            for match_up in &match_ups {
+                for &(side, statistics) in &[("first", &match_up.first), ("second", &match_up.second)] {
+                    let or_empty = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+                    println!("{},{},{},{},{},{},{},{},{}", match_up.first.contender, match_up.second.contender, match_up.games,
+                             side, statistics.contender, statistics.wins, statistics.win_rate,
+                             or_empty(statistics.mean_shots_to_win), or_empty(statistics.median_shots_to_win));
+                }
+            }
+        },
+        Format::HistogramCsv => {
+            println!("first,second,side,contender,shots,wins");
+            for match_up in &match_ups {
+                for &(side, statistics) in &[("first", &match_up.first), ("second", &match_up.second)] {
+                    for (shots, wins) in &statistics.histogram {
+                        println!("{},{},{},{},{},{}", match_up.first.contender, match_up.second.contender, side, statistics.contender, shots, wins);
+                    }
+                }
+            }
+        },
+        Format::Json => println!("{}", serde_json::to_string_pretty(&match_ups).unwrap()),
+    }
+}
+
+fn print_match_up(match_up: &battleplanes::MatchUp) {
+    println!("{} vs {}, {} games:", match_up.first.contender, match_up.second.contender, match_up.games);
+    for statistics in &[&match_up.first, &match_up.second] {
+        print!("  {}: {} wins, {:.1}%", statistics.contender, statistics.wins, 100.0 * statistics.win_rate);
+        match (statistics.mean_shots_to_win, statistics.median_shots_to_win) {
+            (Some(mean), Some(median)) => println!(", shots to win: mean {:.1}, median {}", mean, median),
+            _ => println!(),
+        }
+        // Buckets of 5 shots, the bars scaled to the fullest bucket.
+        let mut buckets: std::collections::BTreeMap<usize, usize> = std::collections::BTreeMap::new();
+        for (shots, wins) in &statistics.histogram {
+            *buckets.entry(shots / 5 * 5).or_insert(0) += *wins;
+        }
+        let fullest = buckets.values().cloned().max().unwrap_or(1);
+        for (bucket, wins) in &buckets {
+            println!("    {:>3}-{:<3} {} {}", bucket, bucket + 4, "#".repeat((wins * 40 + fullest - 1) / fullest), wins);
+        }
+    }
+}
+
+/// How a game between two bots ended, by seat.
+struct Outcome {
+    winner: usize,
+    forfeit: Option<String>,
+}
+
+/// Plays a game between the bots, `seats[player]` being the bot playing as
+/// `player`.
+fn play(options: &Options, seats: &[usize], seed: usize) -> io::Result<Outcome> {
+    let rules = &options.rules;
+    let mut rng = StdRng::from_seed(&[seed]);
+    let mut game = battleplanes::Game::new_random_starter_with_rules(false, rules.clone(), &mut rng);
+    let mut bots = Vec::new();
+    for player in 0..2 {
+        let mut bot = BotProcess::spawn(&options.players[seats[player]], options.timeout)?;
+        bot.introduce(rules, player, seed);
+        bots.push(bot);
+    }
+    let mut last_event = None;
+    let gameplay = {
+        let players: Vec<Box<battleplanes::Player>> = bots.iter_mut()
+            .map(|bot| Box::new(bot) as Box<battleplanes::Player>)
+            .collect();
+        battleplanes::Engine::new(&mut game, players).run(|_, event| last_event = Some(event.clone()))
+    };
+    let outcome = match (gameplay, last_event) {
+        (battleplanes::GamePlay::Won(winner), _) => Outcome { winner: winner, forfeit: None },
+        (_, Some(battleplanes::Event::Waiting(player))) => Outcome {
+            winner: 1 - player,
+            forfeit: bots[player].failure.clone(),
+        },
+        (_, Some(battleplanes::Event::PlacementRejected { player, error })) => Outcome {
+            winner: 1 - player,
+            forfeit: Some(format!("placement rejected, {}", error)),
+        },
+        (_, Some(battleplanes::Event::Bombarded { player, target, .. })) => Outcome {
+            winner: 1 - player,
+            forfeit: Some(format!("cannot bombard {}", target)),
+        },
+        (gameplay, _) => Outcome {
+            winner: 1 - gameplay.player(),
+            forfeit: Some("no move".to_string()),
+        },
+    };
+    for bot in bots.iter_mut() {
+        bot.catch_up(&game);
+        bot.send(&format!("gameover {}", outcome.winner));
+        bot.stop();
+    }
+    Ok(outcome)
+}
+
+/// A bot running as a child process, which lines are read from by a thread
+/// of its own so that the arena can stop waiting for them.
+struct BotProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    lines: Receiver<String>,
+    timeout: Duration,
+    /// How many moves of the game the bot was told about.
+    moves_sent: usize,
+    /// Why the bot failed to answer, if it did.
+    failure: Option<String>,
+}
+
+impl BotProcess {
+    fn spawn(command: &str, timeout: Duration) -> io::Result<BotProcess> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let (program, args) = match words.split_first() {
+            Some(split) => split,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty bot command")),
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if sender.send(line).is_err() { break; },
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(BotProcess {
+            stdin: child.stdin.take(),
+            child: child,
+            lines: receiver,
+            timeout: timeout,
+            moves_sent: 0,
+            failure: None,
+        })
+    }
+    /// Tells the bot the rules of a game in which it plays as `player`.
+    fn introduce(&mut self, rules: &battleplanes::Rules, player: usize, seed: usize) {
+        self.send(&format!("game {} {} {}", player, rules.spec, seed));
+        let mut shapes: Vec<&battleplanes::PlaneShape> = Vec::new();
+        for fleet in &rules.fleets {
+            for shape in fleet.distinct_shapes() {
+                if !shapes.contains(&shape) {
+                    shapes.push(shape);
+                }
+            }
+        }
+        for shape in shapes {
+            let body: Vec<String> = shape.body().iter().map(|&(x, y)| format!("{},{}", x, y)).collect();
+            self.send(&format!("shape {} {}", shape.name(), body.join(" ")));
+        }
+        self.send(&format!("fleet {}", shape_names(&rules.fleets[player])));
+        self.send(&format!("opponent {}", shape_names(rules.opponent_fleet(player))));
+    }
+    /// Sends the bombardments the bot has not heard of yet.
+    fn catch_up(&mut self, game: &battleplanes::Game) {
+        let new_moves: Vec<battleplanes::Move> = game.moves[self.moves_sent..].to_vec();
+        self.moves_sent = game.moves.len();
+        for new_move in new_moves {
+            if let battleplanes::Move::Bombarded { player, target, result } = new_move {
+                let result = match result {
+                    battleplanes::BombardmentResult::Hit => "hit".to_string(),
+                    battleplanes::BombardmentResult::Miss => "miss".to_string(),
+                    battleplanes::BombardmentResult::Kill(shape) => format!("kill {}", shape),
+                    battleplanes::BombardmentResult::Retry => continue,
+                };
+                self.send(&format!("shot {} {} {}", player, target, result));
+            }
+        }
+    }
+    fn send(&mut self, line: &str) {
+        let sent = match self.stdin {
+            Some(ref mut stdin) => writeln!(stdin, "{}", line).and_then(|_| stdin.flush()).is_ok(),
+            None => false,
+        };
+        if !sent && self.failure.is_none() {
+            self.failure = Some("stopped reading".to_string());
+        }
+    }
+    /// Sends a request and waits for the answer, `None` if there is none in
+    /// time, in which case the failure is recorded.
+    fn ask(&mut self, request: &str) -> Option<String> {
+        if self.failure.is_some() {
+            return None;
+        }
+        self.send(request);
+        match self.lines.recv_timeout(self.timeout) {
+            Ok(line) => Some(line.trim().to_string()),
+            Err(RecvTimeoutError::Timeout) => {
+                self.failure = Some(format!("no answer to {} in time", request));
+                None
+            },
+            Err(RecvTimeoutError::Disconnected) => {
+                self.failure = Some("exited".to_string());
+                None
+            },
+        }
+    }
+    fn stop(&mut self) {
+        self.stdin = None;
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl battleplanes::Player for BotProcess {
+    fn next_plane(&mut self, game: &battleplanes::Game, me: usize) -> Option<(battleplanes::Coordinate, battleplanes::Orientation)> {
+        self.catch_up(game);
+        let shape = match game.players[me].board.next_shape() {
+            Some(shape) => shape.name().to_string(),
+            None => return None,
+        };
+        let answer = match self.ask(&format!("place {}", shape)) {
+            Some(answer) => answer,
+            None => return None,
+        };
+        let split = answer.char_indices().last().map(|(i, _)| i).unwrap_or(0);
+        let (head, orientation) = answer.split_at(split);
+        match (battleplanes::Coordinate::new(head), battleplanes::Orientation::new(orientation)) {
+            (Some(head), Some(orientation)) => Some((head, orientation)),
+            _ => {
+                self.failure = Some(format!("answered {:?} to place", answer));
+                None
+            },
+        }
+    }
+    fn next_target(&mut self, game: &battleplanes::Game, _me: usize) -> Option<battleplanes::Coordinate> {
+        self.catch_up(game);
+        let answer = match self.ask("fire") {
+            Some(answer) => answer,
+            None => return None,
+        };
+        let target = battleplanes::Coordinate::new(answer.as_str());
+        if target.is_none() {
+            self.failure = Some(format!("answered {:?} to fire", answer));
+        }
+        target
+    }
+}
+
+fn shape_names(fleet: &battleplanes::Fleet) -> String {
+    let names: Vec<&str> = fleet.shapes().iter().map(|shape| shape.name()).collect();
+    names.join(" ")
+}
+
+/// The reference bot: plays the protocol on stdin and stdout like the AI of
+/// the given level.
+fn run_bot(level: battleplanes::AiLevel) {
+    let stdin = io::stdin();
+    let mut me = 0;
+    let mut spec = battleplanes::BoardSpec::default();
+    let mut seed = 0;
+    let mut shapes: Vec<battleplanes::PlaneShape> = Vec::new();
+    let mut fleet = battleplanes::Fleet::default();
+    let mut game = battleplanes::Game::new_with_starter(2, 0, false);
+    let mut ai = battleplanes::AiPlayer::new_random_with_level(level);
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (command, args) = match words.split_first() {
+            Some((command, args)) => (*command, args),
+            None => continue,
+        };
+        let answer = match (command, args) {
+            ("game", &[player, size, game_seed]) => {
+                me = player.parse().unwrap_or(0);
+                spec = battleplanes::BoardSpec::new_from_str(size).unwrap_or(spec);
+                seed = game_seed.parse().unwrap_or(0);
+                None
+            },
+            ("shape", _) if !args.is_empty() => {
+                let body: Vec<(i32, i32)> = args[1..].iter()
+                    .filter_map(|offset| {
+                        let mut xy = offset.splitn(2, ',').map(|n| n.parse::<i32>().ok());
+                        match (xy.next(), xy.next()) {
+                            (Some(Some(x)), Some(Some(y))) => Some((x, y)),
+                            _ => None,
+                        }
+                    })
+                    .collect();
+                shapes.extend(battleplanes::PlaneShape::new(args[0], body));
+                None
+            },
+            ("fleet", names) => {
+                fleet = fleet_from_names(&shapes, names);
+                None
+            },
+            ("opponent", names) => {
+                let mut fleets = vec![fleet.clone(), fleet.clone()];
+                fleets[1 - me] = fleet_from_names(&shapes, names);
+                let rules = battleplanes::Rules { spec: spec, fleets: fleets };
+                let mut rng = StdRng::from_seed(&[seed]);
+                game = battleplanes::Game::new_with_rules(me, false, rules.clone());
+                ai = battleplanes::AiPlayer::new_random_with_rules(level, &rules, me, &mut rng);
+                None
+            },
+            ("place", _) => {
+                use battleplanes::Player;
+                ai.next_plane(&game, me).map(|(head, orientation)| {
+                    let _ = game.players[me].board.add_new_plane(head, orientation.clone());
+                    format!("{}{}", head, orientation)
+                })
+            },
+            ("fire", _) => {
+                use battleplanes::Player;
+                ai.next_target(&game, me).map(|target| target.to_string())
+            },
+            ("shot", _) if args.len() >= 3 && args[0].parse() == Ok(me) => {
+                if let Some(target) = battleplanes::Coordinate::new(args[1]) {
+                    record_shot(&mut game.players[me].scrapbook, target, args[2]);
+                }
+                None
+            },
+            ("gameover", _) => break,
+            _ => None,
+        };
+        if let Some(answer) = answer {
+            println!("{}", answer);
+            let _ = io::stdout().flush();
+        }
+    }
+}
+
+fn fleet_from_names(shapes: &[battleplanes::PlaneShape], names: &[&str]) -> battleplanes::Fleet {
+    battleplanes::Fleet::new(names.iter()
+        .filter_map(|name| shapes.iter().find(|shape| shape.name() == *name).cloned())
+        .collect())
+}
+
+/// Writes down the outcome of one of our shots, as `Game::bombard` does.
+fn record_shot(scrapbook: &mut battleplanes::Board, target: battleplanes::Coordinate, result: &str) {
+    let spec = *scrapbook.spec();
+    scrapbook.empty_indices_mut().remove(&target.as_usize(&spec));
+    match result {
+        "hit" => scrapbook.hits_mut().push(target),
+        "miss" => scrapbook.misses_mut().push(target),
+        "kill" => {
+            scrapbook.kills_mut().push(target);
+            scrapbook.hits_mut().push(target);
+        },
+        _ => { },
+    }
+}
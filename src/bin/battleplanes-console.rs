@@ -1,99 +1,366 @@
 extern crate battleplanes;
+extern crate rand;
 
 use std::io;
 use std::fmt;
 use std::io::Write;
 
-fn main() {
-    let mut game = battleplanes::Game::new_random_starter(false);
-    let ai_board = battleplanes::Board::new_random();
-    println!("AI boards");
-    println!("{}", player_boards_as_string(&ai_board, &game.scrapbook_opponent));
-    loop {
-        match game.gameplay {
-            battleplanes::GamePlay::YouPlaceNewPlane => {
-                println!("Your boards");
-                println!("{}", player_boards_as_string(&game.board_you, &game.scrapbook_you));
-                let new_head = read_line_with_prompt("new plane at: ");
-                let new_orientation = read_line_with_prompt("orientation: ");
-                match game.board_you.add_new_plane_at(new_head.as_str(), new_orientation.as_str()) {
-                    Ok(_) => {
-                        game.next_logical_state();
-                    },
-                    Err(msg) => {
-                        println!("{}", msg);
-                        continue
-                    }
-                };
+use rand::{Rng, SeedableRng, StdRng};
+
+const HUMAN: usize = 0;
+const AI: usize = 1;
+
+struct Options {
+    level: battleplanes::AiLevel,
+    rules: battleplanes::Rules,
+    seed: Option<usize>,
+    resume: Option<String>,
+    replay: Option<String>,
+    /// Where the AI keeps what it learns about the human from game to game.
+    stats: Option<String>,
+    /// The planes the human sends to mission without being asked.
+    layout: Option<battleplanes::Board>,
+}
+
+fn parse_args() -> Options {
+    let mut options = Options {
+        level: battleplanes::AiLevel::default(),
+        rules: battleplanes::Rules::default(),
+        seed: None,
+        resume: None,
+        replay: None,
+        stats: None,
+        layout: None,
+    };
+    let mut spec = battleplanes::BoardSpec::default();
+    let mut raw_fleet = None;
+    let mut raw_layout = None;
+    let mut shape = battleplanes::PlaneShape::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--level" => {
+                let raw_level = args.next().unwrap_or("".to_string());
+                match battleplanes::AiLevel::new(raw_level.as_str()) {
+                    Some(level) => options.level = level,
+                    None => usage(&format!("unknown AI level: {}", raw_level)),
+                }
             },
-            battleplanes::GamePlay::OpponentPlacesNewPlane => {
-                let current_index = &game.board_opponent.planes().len();
-                let new_plane = &ai_board.planes()[*current_index];
-                let new_head = format!("{}", new_plane.head());
-                let new_orientation = format!("{}", new_plane.orientation());
-                match game.board_opponent.add_new_plane_at(new_head.as_str(), new_orientation.as_str()) {
-                    Ok(_) => {
-                        println!("AI boards");
-                        println!("{}", player_boards_as_string(&game.board_opponent, &game.scrapbook_opponent));
-                        game.next_logical_state();
-                    },
-                    Err(msg) => {
-                        println!("{}", msg);
-                        continue
-                    }
-                };
+            "--size" => {
+                let raw_spec = args.next().unwrap_or("".to_string());
+                match battleplanes::BoardSpec::new_from_str(raw_spec.as_str()) {
+                    Some(new_spec) => spec = new_spec,
+                    None => usage(&format!("invalid map size: {}, sides go from {} to {}", raw_spec, battleplanes::MIN_SIZE, battleplanes::MAX_SIZE)),
+                }
             },
-            battleplanes::GamePlay::YouBombard => {
-                //dev mode
-                println!("AI boards");
-                println!("{}", player_boards_as_string(&game.board_opponent, &game.scrapbook_opponent));
-                //end dev mode
-                println!("Your boards");
-                println!("{}", player_boards_as_string(&game.board_you, &game.scrapbook_you));
-                let new_hit = read_line_with_prompt("Bombard coordinate: ");
-                match game.you_hit_at(new_hit.as_str()) {
-                    battleplanes::BombardmentResult::Hit => {
-                        println!("You've hit at {}", new_hit);
-                        game.next_logical_state();
-                    },
-                    battleplanes::BombardmentResult::Miss => {
-                        println!("You've missed at {}", new_hit);
-                        game.next_logical_state();
-                    },
-                    battleplanes::BombardmentResult::Kill => {
-                        println!("You've killed at {}", new_hit);
-                        game.next_logical_state();
-                    },
-                    battleplanes::BombardmentResult::Retry => {
-                        println!("Retry");
+            "--fleet" => {
+                match args.next() {
+                    Some(fleet) => raw_fleet = Some(fleet),
+                    None => usage("--fleet needs a fleet, e.g. 3 or \"1 bomber, 2 fighters\""),
+                }
+            },
+            "--shape" => {
+                let raw_shape = args.next().unwrap_or("".to_string());
+                shape = match battleplanes::PlaneShape::new_named(raw_shape.as_str()) {
+                    Some(shape) => shape,
+                    None => match battleplanes::PlaneShape::load_from(&raw_shape) {
+                        Ok(shape) => shape,
+                        Err(error) => usage(&format!("cannot read the plane shape {}: {}", raw_shape, error)),
                     },
                 };
             },
-            battleplanes::GamePlay::OpponentBombards => {
-                let (result, tile) = game.opponent_hits_randomly();
-                match result {
-                    battleplanes::BombardmentResult::Hit => {
-                        println!("AI hits at {}", tile.unwrap())
-                    },
-                    battleplanes::BombardmentResult::Miss => {
-                        println!("AI misses at {}", tile.unwrap())
-                    },
-                    battleplanes::BombardmentResult::Kill => {
-                        println!("AI kills your plane at {}", tile.unwrap())
+            "--layout" => {
+                match args.next() {
+                    Some(layout) => raw_layout = Some(layout),
+                    None => usage("--layout needs planes, e.g. \"C2N H2N E6N\""),
+                }
+            },
+            "--seed" => {
+                let raw_seed = args.next().unwrap_or("".to_string());
+                match raw_seed.parse::<usize>() {
+                    Ok(seed) => options.seed = Some(seed),
+                    Err(_) => usage(&format!("invalid seed: {}", raw_seed)),
+                }
+            },
+            "--resume" => {
+                match args.next() {
+                    Some(path) => options.resume = Some(path),
+                    None => usage("--resume needs a file"),
+                }
+            },
+            "--replay" => {
+                match args.next() {
+                    Some(path) => options.replay = Some(path),
+                    None => usage("--replay needs a file"),
+                }
+            },
+            "--stats" => {
+                match args.next() {
+                    Some(path) => options.stats = Some(path),
+                    None => usage("--stats needs a file"),
+                }
+            },
+            _ => usage(&format!("unknown argument: {}", arg)),
+        }
+    }
+    let raw_fleet = raw_fleet.unwrap_or(battleplanes::DEFAULT_FLEET_SIZE.to_string());
+    let max_fleet_size = battleplanes::Rules::max_fleet_size(&spec, &shape);
+    match battleplanes::Rules::new_from_fleet_str(spec, shape.clone(), raw_fleet.as_str()).filter(battleplanes::Rules::can_be_laid_out) {
+        Some(rules) => options.rules = rules,
+        None => usage(&format!("invalid fleet: {}, fleets of {} planes on a {} map go from 1 to {} planes which fit on it together, mixed fleets like \"1 bomber, 2 fighters\" cover at most half of it", raw_fleet, shape.name(), spec, max_fleet_size)),
+    }
+    if let Some(raw_layout) = raw_layout {
+        let fleet = options.rules.fleets[HUMAN].clone();
+        match battleplanes::Board::from_layout_str_with_fleet(spec, fleet, raw_layout.as_str()) {
+            Ok(layout) => options.layout = Some(layout),
+            Err(errors) => {
+                let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                usage(&format!("invalid layout: {}", errors.join("; ")))
+            },
+        }
+    }
+    options
+}
+
+fn usage(error: &str) -> ! {
+    println!("{}", error);
+    println!("usage: battleplanes-console [--level beginner|intermediate|advanced|hack|auto|adaptive] [--size WIDTHxHEIGHT] [--fleet FLEET[:AI_FLEET]] [--shape classic|fighter|bomber|FILE] [--layout \"C2N H2N E6N\"] [--seed NUMBER] [--resume FILE | --replay FILE] [--stats FILE]");
+    std::process::exit(1);
+}
+
+fn main() {
+    let options = parse_args();
+    if let Some(ref path) = options.replay {
+        match battleplanes::SavedGame::load_from(path) {
+            Ok(saved) => replay(&battleplanes::Replay::new(&saved.game)),
+            Err(error) => usage(&format!("cannot replay {}: {}", path, error)),
+        }
+        return;
+    }
+    let mut stats = match options.stats {
+        Some(ref path) => match battleplanes::PlayerStats::load_from(path) {
+            Ok(stats) => stats,
+            Err(error) => usage(&format!("cannot read the stats in {}: {}", path, error)),
+        },
+        None => battleplanes::PlayerStats::new(),
+    };
+    let (mut game, mut ai) = match options.resume {
+        Some(ref path) => match battleplanes::SavedGame::load_from(path) {
+            Ok(saved) => {
+                let ai = saved.ai_player();
+                (saved.game, ai)
+            },
+            Err(error) => usage(&format!("cannot resume from {}: {}", path, error)),
+        },
+        None => {
+            let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+            let mut rng = StdRng::from_seed(&[seed]);
+            let game = battleplanes::Game::new_random_starter_with_rules(false, options.rules.clone(), &mut rng);
+            let ai = battleplanes::AiPlayer::new_random_with_stats(options.level, &options.rules, AI, &stats, &mut rng);
+            println!("Seed: {}", seed);
+            (game, ai)
+        },
+    };
+    println!("AI level: {}", ai.level());
+    println!("AI boards");
+    println!("{}", player_boards_as_string(ai.layout(), &game.players[AI].scrapbook));
+    // A resumed game may be played by other rules than the layout was read for.
+    let layout = if options.resume.is_none() { options.layout.clone() } else { None };
+    let mut human = ConsolePlayer { command: None, layout: layout };
+    let mut level = ai.level();
+    loop {
+        {
+            let players: Vec<Box<battleplanes::Player>> = vec![Box::new(&mut human), Box::new(&mut ai)];
+            battleplanes::Engine::new(&mut game, players).run(print_event);
+        }
+        if ai.level() != level {
+            level = ai.level();
+            println!("The AI rated your layout at the {} level", level);
+        }
+        match human.command.take() {
+            Some(Command::Save(path)) => {
+                match battleplanes::SavedGame::new(&game, &ai).save_to(&path) {
+                    Ok(_) => println!("Game saved to {}", path),
+                    Err(error) => println!("Cannot save to {}: {}", path, error),
+                }
+            },
+            Some(Command::Load(path)) => {
+                match battleplanes::SavedGame::load_from(&path) {
+                    Ok(saved) => {
+                        ai = saved.ai_player();
+                        level = ai.level();
+                        game = saved.game;
+                        println!("Game loaded from {}, AI level: {}", path, ai.level());
                     },
-                    _ => { },
-                };
-                game.next_logical_state();
+                    Err(error) => println!("Cannot load from {}: {}", path, error),
+                }
             },
-            battleplanes::GamePlay::YouWon => {
+            None => { },
+        }
+        match game.gameplay {
+            battleplanes::GamePlay::Won(HUMAN) => {
                 println!("Congratulations, you have won the game!");
                 break;
             },
-            battleplanes::GamePlay::OpponentWon => {
+            battleplanes::GamePlay::Won(_) => {
                 println!("Unfortunately, the AI defeated you");
                 break;
             },
-        };
+            _ => { },
+        }
+    }
+    if let Some(ref path) = options.stats {
+        stats.record_game(&game, HUMAN);
+        if let Err(error) = stats.save_to(path) {
+            println!("Cannot save the stats to {}: {}", path, error);
+        }
+    }
+}
+
+/// Steps through a saved game, one ply at a time.
+fn replay(replay: &battleplanes::Replay) {
+    let mut ply = 0;
+    loop {
+        let game = replay.game_at(ply);
+        match replay.action_at(ply) {
+            Some(action) => println!("Ply {}/{}: {}", ply, replay.len(), action),
+            None => println!("Ply {}/{}: start of the game", ply, replay.len()),
+        }
+        println!("Your boards");
+        println!("{}", player_boards_as_string(&game.players[HUMAN].board, &game.players[HUMAN].scrapbook));
+        println!("AI boards");
+        println!("{}", player_boards_as_string(&game.players[AI].board, &game.players[AI].scrapbook));
+        let input = read_line_with_prompt("[n]ext, [p]revious, ply number or [q]uit: ");
+        match input.as_str() {
+            "n" if ply < replay.len() => ply += 1,
+            "p" if ply > 0 => ply -= 1,
+            "n" | "p" => println!("No more plies that way"),
+            "q" => break,
+            _ => match input.parse::<usize>() {
+                Ok(wanted) if wanted <= replay.len() => ply = wanted,
+                _ => println!("Unknown command: {}", input),
+            },
+        }
+    }
+}
+
+fn print_event(game: &battleplanes::Game, event: &battleplanes::Event) {
+    match event {
+        &battleplanes::Event::PlanePlaced { player: AI, .. } => {
+            println!("AI boards");
+            println!("{}", player_boards_as_string(&game.players[AI].board, &game.players[AI].scrapbook));
+        },
+        &battleplanes::Event::PlacementRejected { player: HUMAN, ref error } => {
+            println!("{}", placement_error_message(error, game.spec()));
+        },
+        &battleplanes::Event::Bombarded { player, target, ref result } => {
+            let who = if player == HUMAN { "You've" } else { "AI" };
+            match result {
+                &battleplanes::BombardmentResult::Hit => println!("{} hit at {}", who, target),
+                &battleplanes::BombardmentResult::Miss => println!("{} missed at {}", who, target),
+                &battleplanes::BombardmentResult::Kill(ref shape) => println!("{} killed a {} at {}", who, shape, target),
+                &battleplanes::BombardmentResult::Retry => println!("Retry"),
+            }
+        },
+        _ => { },
+    }
+}
+
+/// Something the human typed instead of a move, handled between turns.
+enum Command {
+    Save(String),
+    Load(String),
+}
+
+impl Command {
+    fn new(input: &str) -> Option<Command> {
+        let mut words = input.splitn(2, ' ');
+        match (words.next(), words.next().map(|path| path.trim())) {
+            (Some("save"), Some(path)) if !path.is_empty() => Some(Command::Save(path.to_string())),
+            (Some("load"), Some(path)) if !path.is_empty() => Some(Command::Load(path.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// The human sitting at the terminal.
+struct ConsolePlayer {
+    command: Option<Command>,
+    layout: Option<battleplanes::Board>,
+}
+
+impl battleplanes::Player for ConsolePlayer {
+    fn next_plane(&mut self, game: &battleplanes::Game, me: usize) -> Option<(battleplanes::Coordinate, battleplanes::Orientation)> {
+        let current_index = game.players[me].board.planes().len();
+        let planned = self.layout.as_ref().and_then(|layout| layout.planes().get(current_index));
+        if let Some(plane) = planned {
+            return Some((*plane.head(), plane.orientation().clone()));
+        }
+        println!("Your boards");
+        println!("{}", player_boards_as_string(&game.players[me].board, &game.players[me].scrapbook));
+        let board = &game.players[me].board;
+        let shape = board.next_shape().map(|shape| shape.name()).unwrap_or("plane");
+        let prompt = format!("new {} ({} of {}) at (or save/load FILE): ", shape, board.planes().len() + 1, board.fleet_size());
+        let new_head = read_line_with_prompt(&prompt);
+        if let Some(command) = Command::new(&new_head) {
+            self.command = Some(command);
+            return None;
+        }
+        let new_orientation = read_line_with_prompt("orientation: ");
+        match (battleplanes::Coordinate::new(new_head.as_str()), battleplanes::Orientation::new(new_orientation.as_str())) {
+            (Some(head), Some(orientation)) => Some((head, orientation)),
+            (None, _) => {
+                println!("{}", placement_error_message(&battleplanes::PlacementError::InvalidCoordinate(new_head), game.spec()));
+                None
+            },
+            (_, None) => {
+                println!("{}", placement_error_message(&battleplanes::PlacementError::InvalidOrientation(new_orientation), game.spec()));
+                None
+            },
+        }
+    }
+    fn next_target(&mut self, game: &battleplanes::Game, me: usize) -> Option<battleplanes::Coordinate> {
+        //dev mode
+        println!("AI boards");
+        println!("{}", player_boards_as_string(&game.players[AI].board, &game.players[AI].scrapbook));
+        //end dev mode
+        println!("Your boards");
+        println!("{}", player_boards_as_string(&game.players[me].board, &game.players[me].scrapbook));
+        let new_hit = read_line_with_prompt("Bombard coordinate (or hint, save/load FILE): ");
+        if let Some(command) = Command::new(&new_hit) {
+            self.command = Some(command);
+            return None;
+        }
+        if new_hit == "hint" {
+            println!("Chances of hitting a plane, in percent");
+            println!("{}", battleplanes::Heatmap::new(&game.players[me].scrapbook));
+            return None;
+        }
+        match battleplanes::Coordinate::new(new_hit.as_str()) {
+            Some(coord) => Some(coord),
+            None => {
+                println!("Retry");
+                None
+            },
+        }
+    }
+}
+
+fn placement_error_message(error: &battleplanes::PlacementError, spec: &battleplanes::BoardSpec) -> String {
+    use battleplanes::PlacementError::*;
+    match error {
+        &InvalidCoordinate(ref head) => {
+            let last_letter = battleplanes::CoordLetter::from(spec.width - 1);
+            format!("{} is not on the map, use a letter A-{} followed by a number 1-{}, e.g. E5.", head, last_letter, spec.height)
+        },
+        &InvalidOrientation(ref orientation) => format!("{} is not an orientation, use one of N, E, S or W.", orientation),
+        &OutOfMap => "Plane would fall off the map, try again.".to_string(),
+        &Overlap { ref with } => {
+            let ids: Vec<String> = with.iter().map(|id| id.to_string()).collect();
+            format!("Plane would overlap with your plane(s) {}, try again.", ids.join(", "))
+        },
+        &GameAlreadyStarted => "Cannot add planes mid-game".to_string(),
+        &TooManyPlanes => "All your planes are already on a mission.".to_string(),
     }
 }
 
@@ -114,25 +381,38 @@ fn read_line_with_prompt(prompt: &str) -> String {
 }
 
 fn player_boards_as_string(left: &battleplanes::Board, right: &battleplanes::Board) -> String {
-    let template = format!("
-  ABCDEFGHIJ     ABCDEFGHIJ  
- 1          1   1          1 
- 2          2   2          2 
- 3          3   3          3 
- 4          4   4          4 
- 5          5   5          5 
- 6          6   6          6 
- 7          7   7          7 
- 8          8   8          8 
- 9          9   9          9 
-10          10 10          10
-  ABCDEFGHIJ     ABCDEFGHIJ  
-");
+    boards_as_string(&[left, right])
+}
+
+/// The boards side by side, framed by the letters of the columns and the
+/// numbers of the rows, e.g. for two 10x10 boards:
+///
+/// ```text
+///   ABCDEFGHIJ     ABCDEFGHIJ
+///  1          1   1          1
+/// ...
+/// 10          10 10          10
+///   ABCDEFGHIJ     ABCDEFGHIJ
+/// ```
+fn boards_as_string(boards: &[&battleplanes::Board]) -> String {
+    let spec = boards[0].spec();
+    let letters: String = (0..spec.width).map(|x| battleplanes::CoordLetter::from(x).to_string()).collect();
+    let blank = " ".repeat(spec.width);
+    let header: Vec<String> = boards.iter().map(|_| format!("  {}  ", letters)).collect();
+    let header = header.join(" ");
+    let mut template = format!("\n{}\n", header);
+    for y in 0..spec.height {
+        let row: Vec<String> = boards.iter().map(|_| format!("{:>2}{}{:<2}", y + 1, blank, y + 1)).collect();
+        template.push_str(&format!("{}\n", row.join(" ")));
+    }
+    template.push_str(&format!("{}\n", header));
+
+    let line_length = header.len() + 1;
     let mut board_data = template.into_bytes();
-    let board_heuristic_left = |x:usize, y:usize| 33 + y*30 + x;
-    let board_heuristic_right = |x:usize, y:usize| 48 + y*30 + x;
-    draw_planes_onto_data(&mut board_data, &left, board_heuristic_left);
-    draw_planes_onto_data(&mut board_data, &right, board_heuristic_right);
+    for (i, board) in boards.iter().enumerate() {
+        let first_tile = 1 + line_length + 2 + i * (spec.width + 5);
+        draw_planes_onto_data(&mut board_data, board, |x:usize, y:usize| first_tile + y*line_length + x);
+    }
     String::from_utf8(board_data).unwrap()
 }
 
@@ -194,51 +474,7 @@ impl ConsoleBoard<battleplanes::Board> {
 
 impl fmt::Display for ConsoleBoard<battleplanes::Board> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let ascii_board = format!("
-  ABCDEFGHIJ
- 1          1 
- 2          2 
- 3          3 
- 4          4 
- 5          5 
- 6          6 
- 7          7 
- 8          8 
- 9          9 
-10          10
-  ABCDEFGHIJ
-");
-        let mut byte_grid = ascii_board.into_bytes();
-        let offset_heuristic = |x:usize, y:usize| 16 + y*15 + x;
-        for plane in self.planes() {
-            let head_c : u8 = match plane.orientation() {
-                &battleplanes::Orientation::North => '^' as u8,
-                &battleplanes::Orientation::South => 'v' as u8,
-                &battleplanes::Orientation::West => '<' as u8,
-                &battleplanes::Orientation::East => '>' as u8,
-            };
-            let (head_x, head_y) = plane.head().as_tuple();
-            let head_offset = offset_heuristic(head_x, head_y);
-            byte_grid[head_offset] = head_c;
-
-            for tile in plane.coordinate_iterator().filter_map(|t| t) {
-                let (tile_x, tile_y) = tile.as_tuple();
-                let tile_offset = offset_heuristic(tile_x, tile_y);
-                byte_grid[tile_offset] = 'o' as u8;
-            }
-        }
-        for hit in self.hits() {
-            let (hit_x, hit_y) = hit.as_tuple();
-            let hit_offset = offset_heuristic(hit_x, hit_y);
-            byte_grid[hit_offset] = 'x' as u8;
-        }
-        for miss in self.misses() {
-            let (miss_x, miss_y) = miss.as_tuple();
-            let miss_offset = offset_heuristic(miss_x, miss_y);
-            byte_grid[miss_offset] = '*' as u8;
-        }
-        let displayed = String::from_utf8(byte_grid).unwrap();
-        write!(f, "{}", displayed)
+        write!(f, "{}", boards_as_string(&[&self.0]))
     }
 }
 
@@ -13,11 +13,15 @@ extern crate concurrent_hashmap;
 extern crate plugin;
 extern crate urlparse;
 extern crate iron_send_file;
+extern crate rand;
+#[macro_use]
+extern crate serde_json;
 
 extern crate battleplanes;
 
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use std::fmt;
 
 use iron::prelude::*;
@@ -33,6 +37,12 @@ use iron_sessionstorage::backends::SignedCookieBackend;
 
 use concurrent_hashmap::ConcHashMap;
 
+const HUMAN: usize = 0;
+const AI: usize = 1;
+/// How many requests are answered at once, event streams included, see
+/// `events::MAX_STREAMS`.
+const WORKER_THREADS: usize = 32;
+
 #[derive(Clone)]
 struct SessionId(String);
 
@@ -51,44 +61,139 @@ impl iron_sessionstorage::Value for SessionId {
     }
 }
 
-// TODO: wrap data in the hashmap in Arc's
 #[derive(Clone)]
 pub struct GamePool {
-    games: ConcHashMap<String, battleplanes::Game>,
-    ai_initial_boards: ConcHashMap<String, battleplanes::Board>,
+    /// The games and the AI players, see `store`.
+    store: Arc<store::GameStore>,
+    /// Sessions playing against another human, see `lobby`.
+    seats: ConcHashMap<String, lobby::Seat>,
+    /// Join codes of the matches still waiting for a second player, mapped
+    /// to the key of their game.
+    join_codes: ConcHashMap<String, String>,
+    /// Whoever follows the games live, see `events`.
+    events: Arc<events::EventHub>,
+    /// When each game was last played, see `expiry`.
+    last_activity: ConcHashMap<String, Instant>,
+    /// How long a game may stay idle before it is removed.
+    idle_ttl: Duration,
+    /// How many idle games were removed since the server started.
+    evicted: usize,
+    /// When the stats of each player were last used, see `expiry`.
+    stats_activity: ConcHashMap<String, Instant>,
+    /// How long the stats of a player who stopped playing are kept.
+    stats_ttl: Duration,
 }
 
 impl GamePool {
-    fn find_initial_ai_board(&mut self, key: String) -> battleplanes::Board {
-        match self.ai_initial_boards.find_mut(&key) {
-            Some(mut board) => board.get().clone(),
+    fn new(store: Box<store::GameStore>, idle_ttl: Duration, stats_ttl: Duration) -> GamePool {
+        GamePool {
+            store: Arc::from(store),
+            seats: ConcHashMap::<String, lobby::Seat>::new(),
+            join_codes: ConcHashMap::<String, String>::new(),
+            events: Arc::new(events::EventHub::new()),
+            last_activity: ConcHashMap::<String, Instant>::new(),
+            idle_ttl: idle_ttl,
+            evicted: 0,
+            stats_activity: ConcHashMap::<String, Instant>::new(),
+            stats_ttl: stats_ttl,
+        }
+    }
+    /// The AI player under `key`, with a new layout fitting the `rules` if
+    /// there is none yet, drawing on the stats of the player under `key`.
+    fn find_ai_player(&mut self, key: String, level: battleplanes::AiLevel, rules: &battleplanes::Rules) -> battleplanes::AiPlayer {
+        self.touch(&key);
+        match self.store.find_ai_player(&key) {
+            Some(ai) => ai,
             None => {
-                self.ai_initial_boards.insert(key.clone(), battleplanes::Board::new_random());
-                self.ai_initial_boards.find(&key).unwrap().get().clone()
+                self.touch_stats(&key);
+                let stats = self.store.find_player_stats(&key).unwrap_or_default();
+                let ai = battleplanes::AiPlayer::new_random_with_stats(level, &rules_to_start_with(rules), AI, &stats, &mut rand::thread_rng());
+                self.store.save_ai_player(&key, &ai);
+                ai
             },
         }
     }
-    fn find_game(&mut self, key: String, reveal_killed: bool) -> &mut battleplanes::Game {
-        match self.games.find_mut(&key) {
-            Some(mut game) => game.get(),
+    /// The AI picks its shots with its own random generator, which has to be
+    /// kept between requests.
+    fn store_ai_player(&mut self, key: String, ai: battleplanes::AiPlayer) {
+        self.store.save_ai_player(&key, &ai);
+    }
+    /// Like `find_game`, without starting a new game when there is none.
+    fn find_existing_game(&mut self, key: &String) -> Option<battleplanes::Game> {
+        let game = self.store.find_game(key);
+        if game.is_some() {
+            self.touch(key);
+        }
+        game
+    }
+    /// The game under `key`, started with the `rules` if there is none yet.
+    /// Changes to it only last once given back to `store_game`.
+    fn find_game(&mut self, key: String, reveal_killed: bool, rules: &battleplanes::Rules) -> battleplanes::Game {
+        self.touch(&key);
+        match self.store.find_game(&key) {
+            Some(game) => game,
             None => {
-                self.games.insert(key.clone(), battleplanes::Game::new_random_starter(reveal_killed));
-                self.games.find_mut(&key).unwrap().get()
+                let game = battleplanes::Game::new_random_starter_with_rules(reveal_killed, rules_to_start_with(rules), &mut rand::thread_rng());
+                self.store.save_game(&key, &game);
+                game
             }
         }
     }
-    // TODO: remove the game once it's finished
-    // currently, the heap grows indefinitely
+    fn store_game(&mut self, key: String, game: &battleplanes::Game) {
+        self.touch(&key);
+        self.store.save_game(&key, game);
+    }
+    /// Stores a game played on while the pool was not locked, unless another
+    /// request moved it on from the `moves_before` it was fetched with, or
+    /// removed it, in the meantime; then nothing is stored and `false` is
+    /// returned, so that no move gets lost.
+    fn store_game_if_unchanged(&mut self, key: String, moves_before: usize, game: &battleplanes::Game) -> bool {
+        match self.store.find_game(&key) {
+            Some(ref stored) if stored.moves.len() == moves_before => {
+                self.store_game(key, game);
+                true
+            },
+            _ => false,
+        }
+    }
+    /// Adds a finished game to what the AI knows of the human playing under
+    /// `key`.
+    fn record_game(&mut self, key: String, game: &battleplanes::Game) {
+        self.touch_stats(&key);
+        let mut stats = self.store.find_player_stats(&key).unwrap_or_default();
+        stats.record_game(game, HUMAN);
+        self.store.save_player_stats(&key, &stats);
+    }
+    /// Forgets the game, its AI player, and the seats and join code of its
+    /// match if any.
     fn remove_game(&mut self, key: String) {
-        self.games.remove(&key);
-        self.ai_initial_boards.remove(&key);
+        self.store.remove(&key);
+        self.last_activity.remove(&key);
+        let seated: Vec<String> = self.seats.iter()
+            .filter(|&(_, seat)| seat.game_key == key)
+            .map(|(session, _)| session.clone())
+            .collect();
+        for session in seated {
+            self.seats.remove(&session);
+        }
+        let codes: Vec<String> = self.join_codes.iter()
+            .filter(|&(_, game_key)| *game_key == key)
+            .map(|(code, _)| code.clone())
+            .collect();
+        for code in codes {
+            self.join_codes.remove(&code);
+        }
     }
 }
 impl fmt::Display for GamePool {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut len : usize = 0;
-        for (key, value) in self.ai_initial_boards.iter() {
-            match write!(f, "{}={}\n",key, value) {
+        for key in self.store.game_keys() {
+            let ai = match self.store.find_ai_player(&key) {
+                Some(ai) => format!("{} ({})", ai.layout(), ai.level()),
+                None => "human players".to_string(),
+            };
+            match write!(f, "{}={}\n", key, ai) {
                 Ok(_) => { },
                 Err(_) => {
                     //TODO: abort instead
@@ -106,12 +211,9 @@ pub struct GamePoolMiddleware {
     data: Arc<RwLock<GamePool>>,
 }
 impl GamePoolMiddleware {
-    fn new() -> GamePoolMiddleware {
+    fn new(store: Box<store::GameStore>, idle_ttl: Duration, stats_ttl: Duration) -> GamePoolMiddleware {
         GamePoolMiddleware {
-            data: Arc::new(RwLock::new(GamePool {
-                games: ConcHashMap::<String, battleplanes::Game>::new(),
-                ai_initial_boards: ConcHashMap::<String, battleplanes::Board>::new(),
-            })),
+            data: Arc::new(RwLock::new(GamePool::new(store, idle_ttl, stats_ttl))),
         }
     }
 }
@@ -148,6 +250,21 @@ impl<'a, 'b> GamePoolRequestExt for Request<'a, 'b> {
 }
 */
 
+#[path = "battleplanes-web/api.rs"]
+mod api;
+
+#[path = "battleplanes-web/lobby.rs"]
+mod lobby;
+
+#[path = "battleplanes-web/events.rs"]
+mod events;
+
+#[path = "battleplanes-web/store.rs"]
+mod store;
+
+#[path = "battleplanes-web/expiry.rs"]
+mod expiry;
+
 mod template {
     use maud;
     pub fn with_layout(inner: maud::Markup) -> maud::Markup {
@@ -175,31 +292,29 @@ mod template {
             }
         }
     }
-    pub fn battleplanes_board(board: &::battleplanes::Board, id: &String) -> maud::Markup {
-        let grid = get_normalized_grid(board);
+    pub fn battleplanes_board(board: &::battleplanes::Board, id: &String, hint: Option<&::battleplanes::Heatmap>) -> maud::Markup {
+        let grid = get_normalized_grid(board, hint);
+        let spec = board.spec();
+        // The page previews the plane to place next with this shape.
+        let next_plane_body = board.next_shape()
+            .map(|shape| ::serde_json::to_string(shape.body()).unwrap())
+            .unwrap_or("[]".to_string());
         html! {
-            table.battleplanes-board id=(id) {
+            table.battleplanes-board id=(id) data-plane-body=(next_plane_body) {
                 thead {
                     td { " " }
-                    td { "A" }
-                    td { "B" }
-                    td { "C" }
-                    td { "D" }
-                    td { "E" }
-                    td { "F" }
-                    td { "G" }
-                    td { "H" }
-                    td { "I" }
-                    td { "J" }
+                    @for colnum in 0..spec.width {
+                        td { (::battleplanes::CoordLetter::from(colnum)) }
+                    }
                 }
                 tbody {
-                    @for rownum in 0..10 {
+                    @for rownum in 0..spec.height {
                         tr {
                             th {
                                 (rownum+1)
                             }
-                            @for colnum in 0..10 {
-                                td class=(grid[rownum][colnum].class) { (grid[rownum][colnum].content) }
+                            @for colnum in 0..spec.width {
+                                td class=(grid[rownum][colnum].class) style=(grid[rownum][colnum].style) title=(grid[rownum][colnum].title) { (grid[rownum][colnum].content) }
                             }
                         }
                     }
@@ -208,14 +323,19 @@ mod template {
         }
     }
 
+    /// The boards of the player `me`, on the page at `path`, which receives
+    /// the moves and has the hint under `{path}hint`.
     pub fn player_boards_as_html(left: &::battleplanes::Board,
                                  right: &::battleplanes::Board,
                                  gameplay: &::battleplanes::GamePlay,
+                                 me: usize,
+                                 path: &str,
+                                 hint: Option<&::battleplanes::Heatmap>,
     ) -> maud::Markup {
-        let left_markup = battleplanes_board(left, &"own_board".to_string());
-        let right_markup = battleplanes_board(right, &"own_scrapbook".to_string());
+        let left_markup = battleplanes_board(left, &"own_board".to_string(), None);
+        let right_markup = battleplanes_board(right, &"own_scrapbook".to_string(), hint);
         let left_form = match gameplay {
-            &::battleplanes::GamePlay::YouPlaceNewPlane => {
+            &::battleplanes::GamePlay::PlaceNewPlane(player) if player == me => {
                 html! {
                     form {
                         input name="new_head" id="new_head" type="hidden" /
@@ -230,9 +350,9 @@ mod template {
             }
         };
         let right_form = match gameplay {
-            &::battleplanes::GamePlay::YouBombard => {
+            &::battleplanes::GamePlay::Bombard(player) if player == me => {
                 html! {
-                    form id="bombard_form" {
+                    form id="bombard_form" action=(path) {
                         input name="new_hit" id="new_hit" /
                         input type="submit" value="Bombard" /
                     }
@@ -244,7 +364,7 @@ mod template {
             }
         };
         let top_notice = match gameplay {
-            &::battleplanes::GamePlay::YouPlaceNewPlane => {
+            &::battleplanes::GamePlay::PlaceNewPlane(player) if player == me => {
                 html! {
                     tr {
                         td colspan="2" {
@@ -258,24 +378,36 @@ mod template {
                                 "And click submit to send plane to mission."
                             }
                             p {
-                                "You have " (3-left.planes().len()) " left to send to mission."
+                                "You have " (left.fleet_size() - left.planes().len()) " left to send to mission."
+                            }
+                            @if let Some(shape) = left.next_shape() {
+                                p {
+                                    "Next up: a " (shape.name()) "."
+                                }
                             }
                         }
                     }
                 }
             },
-            &::battleplanes::GamePlay::YouBombard => {
+            &::battleplanes::GamePlay::Bombard(player) if player == me => {
                 html! {
                     tr {
                         td colspan="2" {
                             p {
                                 "Left click on the righthandside board to bombard the opponent."
                             }
+                            p {
+                                @if hint.is_some() {
+                                    a href=(path) { "Hide the hint" }
+                                } @else {
+                                    a href=(format!("{}hint", path)) { "Show where the opponent's planes probably are" }
+                                }
+                            }
                         }
                     }
                 }
             },
-            &::battleplanes::GamePlay::YouWon => {
+            &::battleplanes::GamePlay::Won(player) if player == me => {
                 html! {
                     tr {
                         td colspan="2" {
@@ -284,7 +416,7 @@ mod template {
                     }
                 }
             },
-            &::battleplanes::GamePlay::OpponentWon => {
+            &::battleplanes::GamePlay::Won(_) => {
                 html! {
                     tr {
                         td colspan="2" {
@@ -295,6 +427,15 @@ mod template {
             },
             _ => {
                 html! {
+                    tr {
+                        td colspan="2" {
+                            p {
+                                "Waiting for the opponent to move, "
+                                a href=(path) { "refresh" }
+                                "."
+                            }
+                        }
+                    }
                 }
             },
         };
@@ -316,7 +457,9 @@ mod template {
                     }
                     tr {
                         td.centered {
-                            (left.get_previous_hit_message())
+                            @if let Some(error) = left.previous_error() {
+                                (placement_error_message(error))
+                            }
                         }
                     }
                 }
@@ -324,23 +467,52 @@ mod template {
         }
     }
 
+    fn placement_error_message(error: &::battleplanes::PlacementError) -> String {
+        use battleplanes::PlacementError::*;
+        match error {
+            &InvalidCoordinate(ref head) => format!("{} is not a tile on the board.", head),
+            &InvalidOrientation(ref orientation) => format!("{} is not an orientation, right click to rotate the plane.", orientation),
+            &OutOfMap => "Plane would fall off the map, try again.".to_string(),
+            &Overlap { ref with } => {
+                let ids: Vec<String> = with.iter().map(|id| id.to_string()).collect();
+                format!("Plane would overlap with plane {}, try again.", ids.join(", "))
+            },
+            &GameAlreadyStarted => "Cannot add planes mid-game.".to_string(),
+            &TooManyPlanes => "All your planes are already on a mission.".to_string(),
+        }
+    }
+
     #[derive(Clone)]
     struct HtmlCellProperties {
         class: String,
         content: String,
+        style: String,
+        title: String,
     }
 
-    fn get_normalized_grid(board: &::battleplanes::Board) -> Vec<Vec<HtmlCellProperties>> {
-        let mut grid : Vec<Vec<HtmlCellProperties>> = Vec::with_capacity(10);
-        for i in 0..10 {
+    fn get_normalized_grid(board: &::battleplanes::Board, hint: Option<&::battleplanes::Heatmap>) -> Vec<Vec<HtmlCellProperties>> {
+        let spec = board.spec();
+        let mut grid : Vec<Vec<HtmlCellProperties>> = Vec::with_capacity(spec.height);
+        for i in 0..spec.height {
             grid.push(Vec::new());
-            for _ in 0..10 {
+            for _ in 0..spec.width {
                 grid[i].push(HtmlCellProperties {
                     class: "".to_string(),
                     content: " ".to_string(),
+                    style: "".to_string(),
+                    title: "".to_string(),
                 });
             }
         }
+        if let Some(heatmap) = hint {
+            for tile in board.empty_indices() {
+                let coord = ::battleplanes::Coordinate::new_from_usize(*tile, spec);
+                let (x, y) = coord.as_tuple();
+                let probability = heatmap.plane_probability(&coord);
+                grid[y][x].style = format!("background-color: rgba(255, 0, 0, {:.2})", probability);
+                grid[y][x].title = format!("{:.0}%", probability * 100.0);
+            }
+        }
         for plane in board.planes() {
             let (head_x, head_y) = plane.head().as_tuple();
             grid[head_y][head_x].class = format!("plane-{}", plane.id());
@@ -379,6 +551,15 @@ mod template {
 
         grid
     }
+    /// Wraps a page showing a game, which `script.js` reloads whenever the
+    /// event stream at `events_url` reports a move.
+    pub fn live(inner: maud::Markup, events_url: &str) -> maud::Markup {
+        html! {
+            div id="game" data-events=(events_url) {
+                (inner)
+            }
+        }
+    }
     pub fn single_link_page(title: &String, link: &String) -> maud::Markup {
         html! {
             a href=(link) {
@@ -403,132 +584,181 @@ fn get_session_id(req: &mut Request) -> SessionId {
     }
 }
 
-fn action_index(req: &mut Request) -> IronResult<Response> {
-    let sessionid : SessionId = get_session_id(req);
+/// The AI level asked for with `?level=...`, only used when a new game starts.
+fn get_ai_level(req: &Request) -> battleplanes::AiLevel {
+    let params = match req.url.query() {
+        Some(query) => urlparse::parse_qs(query),
+        None => return battleplanes::AiLevel::default(),
+    };
+    params.get(&"level".to_string())
+        .and_then(|levels| levels.get(0))
+        .and_then(|level| battleplanes::AiLevel::new(level.as_str()))
+        .unwrap_or(battleplanes::AiLevel::default())
+}
 
-    let t = req.get::<GamePoolMiddleware>();
-    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
-    let mut gamepool = arc.write().ok().unwrap();
-    let mut resp = Response::new();
+/// The rules asked for when starting a game: the size of the map with
+/// `?size=`, e.g. `?size=8x8`, the fleets with `?fleet=`, e.g. `?fleet=3:2`
+/// for three planes for the first player and two for the second, and the
+/// planes with `?shape=`, one of `classic`, `fighter` and `bomber`. Rules
+/// which do not fit on the map fall back to three classic planes each, and
+/// so do fleets which cannot be laid out once a game starts, see
+/// `rules_to_start_with`.
+fn get_rules(req: &Request) -> battleplanes::Rules {
+    let params = match req.url.query() {
+        Some(query) => urlparse::parse_qs(query),
+        None => return battleplanes::Rules::default(),
+    };
+    let param = |name: &str| params.get(&name.to_string()).and_then(|values| values.get(0)).cloned();
+    let spec = param("size")
+        .and_then(|size| battleplanes::BoardSpec::new_from_str(size.as_str()))
+        .unwrap_or(battleplanes::BoardSpec::default());
+    let shape = param("shape")
+        .and_then(|shape| battleplanes::PlaneShape::new_named(shape.as_str()))
+        .unwrap_or(battleplanes::PlaneShape::default());
+    let fleet = param("fleet").unwrap_or(battleplanes::DEFAULT_FLEET_SIZE.to_string());
+    battleplanes::Rules::new_from_fleet_str(spec, shape, fleet.as_str())
+        .unwrap_or(battleplanes::Rules { spec: spec, ..battleplanes::Rules::default() })
+}
 
-    let ai_board = { gamepool.find_initial_ai_board(sessionid.clone().to_string()) };
-    println!("{}", ai_board);
-    let mut game = { gamepool.find_game(sessionid.clone().to_string(), true) };
-    match game.gameplay {
-        battleplanes::GamePlay::YouPlaceNewPlane => {
-            match req.url.query() {
-                Some(query) => {
-                    resp.headers.set(iron::headers::Location("/".to_string()));
-                    resp.set_mut(status::Found);
-                    let params = urlparse::parse_qs(query);
-                    match (params.get(&"new_head".to_string()), params.get(&"new_orientation".to_string())) {
-                        (Some(maybe_new_head), Some(maybe_new_orientation)) => {
-                            let new_head = maybe_new_head.get(0).unwrap().as_str();
-                            let new_orientation = maybe_new_orientation.get(0).unwrap().as_str();
-                            match game.board_you.add_new_plane_at(new_head, new_orientation) {
-                                Ok(_) => {
-                                    game.next_logical_state();
-                                    if game.gameplay == battleplanes::GamePlay::OpponentPlacesNewPlane {
-                                        let current_index = &game.board_opponent.planes().len();
-                                        let new_plane = &ai_board.planes()[*current_index];
-                                        let new_head = format!("{}", new_plane.head());
-                                        let new_orientation = format!("{}", new_plane.orientation());
-                                        match game.board_opponent.add_new_plane_at(new_head.as_str(), new_orientation.as_str()) {
-                                            Ok(_) => {
-                                                game.next_logical_state();
-                                            },
-                                            Err(msg) => {
-                                                println!("Error in {} on {}: {}", file!(), line!(), msg);
-                                            }
-                                        };
-                                    }
-                                    if game.gameplay == battleplanes::GamePlay::OpponentBombards {
-                                        game.opponent_hits_randomly();
-                                        game.next_logical_state();
-                                    }
-                                },
-                                Err(msg) => {
-                                    println!("Error in {} on {}: {}", file!(), line!(), msg);
-                                }
-                            };
+/// The `rules` a new game starts with. Whether the fleets can be laid out
+/// may take a search, so it is only checked here and not on every page load.
+fn rules_to_start_with(rules: &battleplanes::Rules) -> battleplanes::Rules {
+    match rules.can_be_laid_out() {
+        true => rules.clone(),
+        false => battleplanes::Rules { spec: rules.spec, ..battleplanes::Rules::default() },
+    }
+}
+
+/// Queues the move which the player `me` submitted through the query string
+/// of a page, if it is their turn. Returns whether a move was submitted, in
+/// which case the page redirects to itself once the move is applied.
+fn queue_submitted_move(req: &Request, gameplay: &battleplanes::GamePlay, me: usize, player: &mut battleplanes::QueuedPlayer) -> bool {
+    let query = match req.url.query() {
+        Some(query) => query,
+        None => return false,
+    };
+    let params = urlparse::parse_qs(query);
+    match gameplay {
+        &battleplanes::GamePlay::PlaceNewPlane(current) if current == me => {
+            match (params.get(&"new_head".to_string()), params.get(&"new_orientation".to_string())) {
+                (Some(maybe_new_head), Some(maybe_new_orientation)) => {
+                    let new_head = maybe_new_head.get(0).unwrap().as_str();
+                    let new_orientation = maybe_new_orientation.get(0).unwrap().as_str();
+                    match (battleplanes::Coordinate::new(new_head), battleplanes::Orientation::new(new_orientation)) {
+                        (Some(head), Some(orientation)) => {
+                            player.push_plane(head, orientation);
                         },
                         _ => {
-                            //TODO: error feedback
-                            println!("Error in {} on {}: invalid head or orientation in query: {}", file!(), line!(), query);
+                            println!("Error in {} on {}: plane cannot spawn at {} in direction {}", file!(), line!(), new_head, new_orientation);
                         },
                     };
                 },
-                None => { },
-            }
-        },
-        battleplanes::GamePlay::OpponentPlacesNewPlane => {
-            let current_index = &game.board_opponent.planes().len();
-            let new_plane = &ai_board.planes()[*current_index];
-            let new_head = format!("{}", new_plane.head());
-            let new_orientation = format!("{}", new_plane.orientation());
-            match game.board_opponent.add_new_plane_at(new_head.as_str(), new_orientation.as_str()) {
-                Ok(_) => {
-                    game.next_logical_state();
+                _ => {
+                    //TODO: error feedback
+                    println!("Error in {} on {}: invalid head or orientation in query: {}", file!(), line!(), query);
                 },
-                Err(msg) => {
-                    println!("Error in {} on {}: {}", file!(), line!(), msg);
-                }
             };
         },
-        battleplanes::GamePlay::YouBombard => {
-            match req.url.query() {
-                Some(query) => {
-                    resp.headers.set(iron::headers::Location("/".to_string()));
-                    resp.set_mut(status::Found);
-                    let params = urlparse::parse_qs(query);
-                    match params.get(&"new_hit".to_string()) {
-                        Some(maybe_new_hit) => {
-                            let new_hit = maybe_new_hit.get(0).unwrap().as_str();
-                            match game.you_hit_at(new_hit) {
-                                battleplanes::BombardmentResult::Hit => {
-                                    println!("You've hit at {}", new_hit);
-                                    game.next_logical_state();
-                                },
-                                battleplanes::BombardmentResult::Miss => {
-                                    println!("You've missed at {}", new_hit);
-                                    game.next_logical_state();
-                                },
-                                battleplanes::BombardmentResult::Kill => {
-                                    println!("You've killed at {}", new_hit);
-                                    game.next_logical_state();
-                                },
-                                battleplanes::BombardmentResult::Retry => {
-                                    println!("Retry");
-                                },
-                            };
-                            game.opponent_hits_randomly();
-                            game.next_logical_state();
-                        },
-                        None => {
-                        },
+        &battleplanes::GamePlay::Bombard(current) if current == me => {
+            match params.get(&"new_hit".to_string()) {
+                Some(maybe_new_hit) => {
+                    let new_hit = maybe_new_hit.get(0).unwrap().as_str();
+                    match battleplanes::Coordinate::new(new_hit) {
+                        Some(target) => player.push_target(target),
+                        None => println!("Retry"),
                     };
                 },
                 None => {
                 },
             };
         },
-        battleplanes::GamePlay::OpponentBombards => {
-            game.opponent_hits_randomly();
-            game.next_logical_state();
-        },
-        battleplanes::GamePlay::YouWon => {
+        _ => return false,
+    }
+    true
+}
+
+fn action_index(req: &mut Request) -> IronResult<Response> {
+    let sessionid : SessionId = get_session_id(req);
+
+    let t = req.get::<GamePoolMiddleware>();
+    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+    let mut resp = Response::new();
+
+    let rules = get_rules(req);
+    let level = get_ai_level(req);
+    // The pool is only locked to fetch and store the game, the AI may take a
+    // while to pick its shot.
+    let (mut ai, mut game, events) = {
+        let mut gamepool = arc.write().ok().unwrap();
+        let ai = gamepool.find_ai_player(sessionid.clone().to_string(), level, &rules);
+        let game = gamepool.find_game(sessionid.clone().to_string(), true, &rules);
+        (ai, game, gamepool.events.clone())
+    };
+    println!("{} ({})", ai.layout(), ai.level());
+    match game.gameplay {
+        battleplanes::GamePlay::Won(HUMAN) => {
             resp.status = Some(iron::status::Found);
             resp.headers.set(iron::headers::Location("/youwon".to_string()));
             return Ok(resp);
         },
-        battleplanes::GamePlay::OpponentWon => {
+        battleplanes::GamePlay::Won(_) => {
             resp.status = Some(iron::status::Found);
             resp.headers.set(iron::headers::Location("/youlost".to_string()));
             return Ok(resp);
         },
+        _ => { },
+    }
+    let mut human = battleplanes::QueuedPlayer::new();
+    if queue_submitted_move(req, &game.gameplay, HUMAN, &mut human) {
+        resp.headers.set(iron::headers::Location("/".to_string()));
+        resp.set_mut(status::Found);
     }
+    let moves_before = game.moves.len();
+    {
+        let players: Vec<Box<battleplanes::Player>> = vec![Box::new(human), Box::new(&mut ai)];
+        battleplanes::Engine::new(&mut game, players).run(|_, event| println!("{:?}", event));
+    }
+    {
+        let mut gamepool = arc.write().ok().unwrap();
+        if !gamepool.store_game_if_unchanged(sessionid.to_string(), moves_before, &game) {
+            // Another request played first, the page shows where it got to.
+            resp.headers.set(iron::headers::Location("/".to_string()));
+            resp.set_mut(status::Found);
+            return Ok(resp);
+        }
+        gamepool.store_ai_player(sessionid.clone().to_string(), ai);
+    }
+    events.publish(&sessionid.to_string(), &game.moves[moves_before..]);
+
+    let index_markup = template::player_boards_as_html(&game.players[HUMAN].board, &game.players[HUMAN].scrapbook, &game.gameplay, HUMAN, "/", None);
+    let index_markup = template::live(index_markup, "/events");
+    let template = template::with_layout(index_markup);
+    try!(req.session().set(sessionid));
+    resp.set_mut(template);
+    Ok(resp)
+}
+
+fn action_hint(req: &mut Request) -> IronResult<Response> {
+    let sessionid : SessionId = get_session_id(req);
+    let mut resp = Response::new();
+
+    let t = req.get::<GamePoolMiddleware>();
+    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+    // Released before working out the heatmap.
+    let game = arc.write().ok().unwrap().find_existing_game(&sessionid.to_string())
+        .filter(|game| game.gameplay == battleplanes::GamePlay::Bombard(HUMAN));
+    let game = match game {
+        Some(game) => game,
+        None => {
+            resp.headers.set(iron::headers::Location("/".to_string()));
+            resp.set_mut(status::Found);
+            return Ok(resp);
+        },
+    };
 
-    let index_markup = template::player_boards_as_html(&game.board_you, &game.scrapbook_you, &game.gameplay);
+    let heatmap = battleplanes::Heatmap::new(&game.players[HUMAN].scrapbook);
+    let index_markup = template::player_boards_as_html(&game.players[HUMAN].board, &game.players[HUMAN].scrapbook, &game.gameplay, HUMAN, "/", Some(&heatmap));
+    let index_markup = template::live(index_markup, "/events");
     let template = template::with_layout(index_markup);
     try!(req.session().set(sessionid));
     resp.set_mut(template);
@@ -542,13 +772,16 @@ fn action_youwon(req: &mut Request) -> IronResult<Response> {
     let t = req.get::<GamePoolMiddleware>();
     let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
     let mut gamepool = arc.write().ok().unwrap();
-    let game = { gamepool.find_game(sessionid.clone().to_string(), true) };
-
-    if game.gameplay != battleplanes::GamePlay::YouWon {
-        resp.headers.set(iron::headers::Location("/".to_string()));
-        resp.set_mut(status::Found);
-        return Ok(resp);
-    }
+    let game = gamepool.find_existing_game(&sessionid.to_string())
+        .filter(|game| game.gameplay == battleplanes::GamePlay::Won(HUMAN));
+    let game = match game {
+        Some(game) => game,
+        None => {
+            resp.headers.set(iron::headers::Location("/".to_string()));
+            resp.set_mut(status::Found);
+            return Ok(resp);
+        },
+    };
 
     match (std::env::var("PRIZE_TITLE"), std::env::var("PRIZE_LINK")) {
         (Ok(link_title), Ok(link_dest)) => {
@@ -563,7 +796,9 @@ fn action_youwon(req: &mut Request) -> IronResult<Response> {
         }
     }
 
-    try!(req.session().clear());
+    // The session is kept, so that the AI remembers the player next game.
+    gamepool.record_game(sessionid.to_string(), &game);
+    gamepool.remove_game(sessionid.to_string());
     Ok(resp)
 }
 
@@ -575,20 +810,24 @@ fn action_youlost(req: &mut Request) -> IronResult<Response> {
     let t = req.get::<GamePoolMiddleware>();
     let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
     let mut gamepool = arc.write().ok().unwrap();
-    let game = { gamepool.find_game(sessionid.clone().to_string(), false) };
-
-    if game.gameplay != battleplanes::GamePlay::OpponentWon {
-        resp.headers.set(iron::headers::Location("/".to_string()));
-        resp.set_mut(status::Found);
-        return Ok(resp);
-    }
+    let game = gamepool.find_existing_game(&sessionid.to_string())
+        .filter(|game| game.gameplay == battleplanes::GamePlay::Won(AI));
+    let game = match game {
+        Some(game) => game,
+        None => {
+            resp.headers.set(iron::headers::Location("/".to_string()));
+            resp.set_mut(status::Found);
+            return Ok(resp);
+        },
+    };
 
 
     let lost_markup = template::single_link_page(&"You Lost, Play Again".to_string(), &"/".to_string());
     let template = template::with_layout(lost_markup);
     resp.set_mut(template);
 
-    try!(req.session().clear());
+    gamepool.record_game(sessionid.to_string(), &game);
+    gamepool.remove_game(sessionid.to_string());
     Ok(resp)
 }
 
@@ -633,6 +872,37 @@ fn get_env() -> (String, String, String) {
     (bind_address, bind_port, session_secret)
 }
 
+/// Which `store::GameStore` to use, and where it keeps its files.
+fn get_game_store_env() -> (String, String) {
+    let game_store = match std::env::var("GAME_STORE") {
+        Ok(val) => val,
+        Err(_) => "memory".to_string(),
+    };
+    let game_store_path = match std::env::var("GAME_STORE_PATH") {
+        Ok(val) => val,
+        Err(_) => "games".to_string(),
+    };
+    (game_store, game_store_path)
+}
+
+/// How long a game may stay idle before the sweeper removes it.
+fn get_idle_ttl_env() -> Duration {
+    let idle_ttl_secs = match std::env::var("GAME_IDLE_TTL_SECS") {
+        Ok(val) => val.parse().expect("GAME_IDLE_TTL_SECS must be a number of seconds"),
+        Err(_) => 3600,
+    };
+    Duration::from_secs(idle_ttl_secs)
+}
+
+/// How long the stats of a player who stopped playing are kept.
+fn get_stats_ttl_env() -> Duration {
+    let stats_ttl_secs = match std::env::var("STATS_IDLE_TTL_SECS") {
+        Ok(val) => val.parse().expect("STATS_IDLE_TTL_SECS must be a number of seconds"),
+        Err(_) => 30 * 24 * 3600,
+    };
+    Duration::from_secs(stats_ttl_secs)
+}
+
 #[cfg(debug_assertions)]
 const ASSETS_PATH: &'static str = "src/bin/battleplanes-web/assets";
 #[cfg(not(debug_assertions))]
@@ -641,14 +911,30 @@ const ASSETS_PATH: &'static str = "assets";
 
 fn main() {
     let (bind_address, bind_port, my_secret) = get_env();
+    let (game_store, game_store_path) = get_game_store_env();
+    let idle_ttl = get_idle_ttl_env();
+    let stats_ttl = get_stats_ttl_env();
     env_logger::init().unwrap();
 
     let mut router = Router::new();
     router.get("/", action_index, "index");
+    router.get("/hint", action_hint, "hint");
     router.get("/youwon", action_youwon, "youwon");
     router.get("/youlost", action_youlost, "youlost");
     router.get("/env", action_env, "env");
+    router.get("/status", expiry::action_status, "status");
     router.get("/favicon.ico", action_favicon, "favicon");
+    router.get("/events", events::action_events, "events");
+    router.get("/match/", lobby::action_match, "match");
+    router.get("/match/events", events::action_match_events, "match_events");
+    router.get("/match/hint", lobby::action_match_hint, "match_hint");
+    router.get("/match/new", lobby::action_match_new, "match_new");
+    router.get("/match/join", lobby::action_match_join, "match_join");
+    router.post("/api/games", api::action_create_game, "api_create_game");
+    router.get("/api/games/:id", api::action_show_game, "api_show_game");
+    router.post("/api/games/:id/planes", api::action_add_plane, "api_add_plane");
+    router.post("/api/games/:id/bombard", api::action_bombard, "api_bombard");
+    router.get("/api/games/:id/events", events::action_api_events, "api_events");
 
     let mut assets_mount = Mount::new();
     assets_mount
@@ -656,7 +942,12 @@ fn main() {
         .mount("/assets/", Static::new(Path::new(ASSETS_PATH)));
     let mut chain = Chain::new(assets_mount);
     chain.link_around(SessionStorage::new(SignedCookieBackend::new(my_secret.into_bytes())));
-    let gamepool = GamePoolMiddleware::new();
+    let store = match store::new_game_store(game_store.as_str(), game_store_path.as_str()) {
+        Ok(store) => store,
+        Err(error) => panic!("{}", error),
+    };
+    let gamepool = GamePoolMiddleware::new(store, idle_ttl, stats_ttl);
+    expiry::spawn_sweeper(gamepool.data.clone());
     chain.link_before(gamepool);
     let p = std::env::current_dir().unwrap();
     println!("The current directory is {}, serving static assets from {}", p.display(), ASSETS_PATH);
@@ -666,5 +957,7 @@ fn main() {
     for (var, val) in std::env::vars() {
         println!("{}={}", var, val);
     }
-    Iron::new(chain).http(endpoint.as_str()).unwrap();
+    let mut server = Iron::new(chain);
+    server.threads = WORKER_THREADS;
+    server.http(endpoint.as_str()).unwrap();
 }
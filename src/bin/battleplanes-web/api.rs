@@ -0,0 +1,248 @@
+//! JSON endpoints for clients which cannot scrape the HTML pages, e.g. bots
+//! and mobile apps. The caller always plays as player 0 against the AI.
+//!
+//! * `POST /api/games` with an optional `{"level": "advanced", "size": "8x8",
+//!   "fleet": "3:2", "shape": "fighter", "reveal_killed": true}` starts a
+//!   game and answers `201` with the game
+//! * `GET /api/games/{id}` answers with the game
+//! * `POST /api/games/{id}/planes` with `{"head": "E5", "orientation": "N"}`
+//! * `POST /api/games/{id}/bombard` with `{"target": "B7"}`
+//!
+//! A game is `{"id": ..., "gameplay": ..., "board": ..., "scrapbook": ...}`,
+//! the player's own board and scrapbook; the AI's board stays hidden. Moves
+//! answer with the `result` of the move, the `gameplay` after it, and the
+//! `moves` it caused, the AI's answer included, with placements hidden as in
+//! the events. The shapes of the game types are those of the `serialize`
+//! module of the library.
+//!
+//! `GET /api/games/{id}/events` streams the moves of the game as server-sent
+//! events, see the `events` module.
+//!
+//! Errors are `{"error": "message"}`: `400` for malformed requests and for
+//! targets which are not on the map, `404` for unknown games, `409` when it
+//! is not the player's turn or another request for the game got there first,
+//! and `422` for moves the rules do not allow, such
+//! as bombarding a tile twice, with the `PlacementError` as `reason` if any.
+use std::io::Read;
+use std::sync::{Arc, RwLock};
+
+use iron::prelude::*;
+use iron::status;
+use iron::mime::Mime;
+use router::Router;
+use serde_json;
+use serde_json::Value;
+use uuid::Uuid;
+
+use battleplanes;
+use {GamePool, GamePoolMiddleware, HUMAN};
+
+pub fn action_create_game(req: &mut Request) -> IronResult<Response> {
+    let body = match read_json(req) {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
+    let level = match body.get("level") {
+        None => battleplanes::AiLevel::default(),
+        Some(raw_level) => match raw_level.as_str().and_then(battleplanes::AiLevel::new) {
+            Some(level) => level,
+            None => return Ok(error_response(status::UnprocessableEntity, &format!("unknown AI level: {}", raw_level))),
+        },
+    };
+    let spec = match body.get("size") {
+        None => battleplanes::BoardSpec::default(),
+        Some(raw_size) => match raw_size.as_str().and_then(battleplanes::BoardSpec::new_from_str) {
+            Some(spec) => spec,
+            None => return Ok(error_response(status::UnprocessableEntity, &format!("invalid map size: {}", raw_size))),
+        },
+    };
+    let shape = match body.get("shape") {
+        None => battleplanes::PlaneShape::default(),
+        Some(raw_shape) => match raw_shape.as_str().and_then(battleplanes::PlaneShape::new_named) {
+            Some(shape) => shape,
+            None => return Ok(error_response(status::UnprocessableEntity, &format!("unknown plane shape: {}", raw_shape))),
+        },
+    };
+    let default_fleet = Value::String(battleplanes::DEFAULT_FLEET_SIZE.to_string());
+    let raw_fleet = body.get("fleet").unwrap_or(&default_fleet);
+    let rules = match raw_fleet.as_str().and_then(|fleet| battleplanes::Rules::new_from_fleet_str(spec, shape, fleet)).filter(battleplanes::Rules::can_be_laid_out) {
+        Some(rules) => rules,
+        None => return Ok(error_response(status::UnprocessableEntity, &format!("invalid fleet: {}", raw_fleet))),
+    };
+    let reveal_killed = body.get("reveal_killed").and_then(|reveal| reveal.as_bool()).unwrap_or(true);
+
+    let id = Uuid::new_v4().hyphenated().to_string();
+    let arc = get_gamepool(req);
+    let mut gamepool = arc.write().ok().unwrap();
+    let events = gamepool.events.clone();
+    let mut ai = gamepool.find_ai_player(id.clone(), level, &rules);
+    let mut game = gamepool.find_game(id.clone(), reveal_killed, &rules);
+    run_engine(&mut game, battleplanes::QueuedPlayer::new(), &mut ai);
+    events.publish(&id, &game.moves);
+    let view = game_view(&id, &game);
+    gamepool.store_game(id.clone(), &game);
+    gamepool.store_ai_player(id, ai);
+    Ok(json_response(status::Created, &view))
+}
+
+pub fn action_show_game(req: &mut Request) -> IronResult<Response> {
+    let id = get_game_id(req);
+    let arc = get_gamepool(req);
+    let mut gamepool = arc.write().ok().unwrap();
+    match gamepool.find_existing_game(&id) {
+        Some(game) => Ok(json_response(status::Ok, &game_view(&id, &game))),
+        None => Ok(error_response(status::NotFound, &format!("no game {}", id))),
+    }
+}
+
+pub fn action_add_plane(req: &mut Request) -> IronResult<Response> {
+    let body = match read_json(req) {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
+    let (head, orientation) = match (body.get("head").and_then(|head| head.as_str()), body.get("orientation").and_then(|orientation| orientation.as_str())) {
+        (Some(head), Some(orientation)) => (head.to_string(), orientation.to_string()),
+        _ => return Ok(error_response(status::BadRequest, "expected {\"head\": ..., \"orientation\": ...}")),
+    };
+    let mut human = battleplanes::QueuedPlayer::new();
+    match (battleplanes::Coordinate::new(&head), battleplanes::Orientation::new(&orientation)) {
+        (Some(head), Some(orientation)) => human.push_plane(head, orientation),
+        (None, _) => return Ok(placement_error_response(&battleplanes::PlacementError::InvalidCoordinate(head))),
+        (_, None) => return Ok(placement_error_response(&battleplanes::PlacementError::InvalidOrientation(orientation))),
+    };
+    play(req, battleplanes::GamePlay::PlaceNewPlane(HUMAN), human)
+}
+
+pub fn action_bombard(req: &mut Request) -> IronResult<Response> {
+    let body = match read_json(req) {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
+    let target = match body.get("target").and_then(|target| target.as_str()) {
+        Some(target) => target.to_string(),
+        None => return Ok(error_response(status::BadRequest, "expected {\"target\": ...}")),
+    };
+    let mut human = battleplanes::QueuedPlayer::new();
+    match battleplanes::Coordinate::new(&target) {
+        Some(target) => human.push_target(target),
+        None => return Ok(invalid_target_response(&target)),
+    };
+    play(req, battleplanes::GamePlay::Bombard(HUMAN), human)
+}
+
+/// Lets the engine apply the move queued for the player, provided it is the
+/// expected kind of turn, and the AI answer.
+fn play(req: &mut Request, expected: battleplanes::GamePlay, human: battleplanes::QueuedPlayer) -> IronResult<Response> {
+    let id = get_game_id(req);
+    let arc = get_gamepool(req);
+    // The pool is only locked to fetch and store the game, the AI may take a
+    // while to pick its shot.
+    let (mut game, mut ai, events) = {
+        let mut gamepool = arc.write().ok().unwrap();
+        let game = match gamepool.find_existing_game(&id) {
+            Some(game) => game,
+            None => return Ok(error_response(status::NotFound, &format!("no game {}", id))),
+        };
+        if game.gameplay != expected {
+            return Ok(error_response(status::Conflict, &format!("cannot do that during {}", game.gameplay)));
+        }
+        let ai = gamepool.find_ai_player(id.clone(), battleplanes::AiLevel::default(), &game.rules());
+        (game, ai, gamepool.events.clone())
+    };
+    let moves_before = game.moves.len();
+    let engine_events = run_engine(&mut game, human, &mut ai);
+    let mut result = Value::Null;
+    for event in &engine_events {
+        match event {
+            &battleplanes::Event::PlacementRejected { player: HUMAN, ref error } => {
+                return Ok(placement_error_response(error));
+            },
+            &battleplanes::Event::Bombarded { player: HUMAN, result: battleplanes::BombardmentResult::Retry, target } => {
+                return Ok(match game.players[HUMAN].scrapbook.spec().contains(&target) {
+                    true => error_response(status::UnprocessableEntity, &format!("{} was already bombarded", target)),
+                    false => invalid_target_response(&target.to_string()),
+                });
+            },
+            &battleplanes::Event::PlanePlaced { player: HUMAN, plane_id } => {
+                result = json!({ "plane_id": plane_id });
+            },
+            &battleplanes::Event::Bombarded { player: HUMAN, target, result: ref outcome } => {
+                result = json!({ "target": target, "result": outcome });
+            },
+            _ => { },
+        }
+    }
+    {
+        let mut gamepool = arc.write().ok().unwrap();
+        if !gamepool.store_game_if_unchanged(id.clone(), moves_before, &game) {
+            return Ok(error_response(status::Conflict, "the game changed meanwhile, try again"));
+        }
+        gamepool.store_ai_player(id.clone(), ai);
+    }
+    events.publish(&id, &game.moves[moves_before..]);
+    Ok(json_response(status::Ok, &json!({
+        "result": result,
+        "gameplay": game.gameplay,
+        "moves": game.moves[moves_before..].iter().map(battleplanes::Move::to_public_json).collect::<Vec<Value>>(),
+    })))
+}
+
+fn run_engine(game: &mut battleplanes::Game, human: battleplanes::QueuedPlayer, ai: &mut battleplanes::AiPlayer) -> Vec<battleplanes::Event> {
+    let mut events = Vec::new();
+    let players: Vec<Box<battleplanes::Player>> = vec![Box::new(human), Box::new(ai)];
+    battleplanes::Engine::new(game, players).run(|_, event| {
+        println!("{:?}", event);
+        events.push(event.clone());
+    });
+    events
+}
+
+fn game_view(id: &String, game: &battleplanes::Game) -> Value {
+    json!({
+        "id": id,
+        "gameplay": game.gameplay,
+        "board": game.players[HUMAN].board,
+        "scrapbook": game.players[HUMAN].scrapbook,
+    })
+}
+
+fn get_gamepool(req: &mut Request) -> Arc<RwLock<GamePool>> {
+    req.get::<GamePoolMiddleware>().ok().unwrap()
+}
+
+fn get_game_id(req: &Request) -> String {
+    req.extensions.get::<Router>().unwrap().find("id").unwrap_or("").to_string()
+}
+
+/// The body of the request, `null` if it is empty.
+fn read_json(req: &mut Request) -> Result<Value, Response> {
+    let mut raw_body = String::new();
+    if let Err(error) = req.body.read_to_string(&mut raw_body) {
+        return Err(error_response(status::BadRequest, &format!("cannot read body: {}", error)));
+    }
+    if raw_body.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_str(&raw_body).map_err(|error| error_response(status::BadRequest, &format!("invalid JSON: {}", error)))
+}
+
+fn json_response(status: status::Status, body: &Value) -> Response {
+    let mime: Mime = "application/json".parse().unwrap();
+    Response::with((status, mime, body.to_string()))
+}
+
+fn error_response(status: status::Status, message: &str) -> Response {
+    json_response(status, &json!({ "error": message }))
+}
+
+/// Told apart from targets which were already bombarded by its `400`.
+fn invalid_target_response(target: &str) -> Response {
+    error_response(status::BadRequest, &format!("{} is not a coordinate on the map", target))
+}
+
+fn placement_error_response(error: &battleplanes::PlacementError) -> Response {
+    json_response(status::UnprocessableEntity, &json!({
+        "error": error.to_string(),
+        "reason": error,
+    }))
+}
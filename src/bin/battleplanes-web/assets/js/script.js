@@ -4,14 +4,19 @@ $(document).ready(function() {
         "This is intended behavior. Various frontends properly designed are planned");
         alert("Left click is short touch. Right click is long touch");
     }
-    window.X_COORDINATES = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"];
+    window.X_COORDINATES = "ABCDEFGHIJKLMNOPQRSTUVWXYZ".split("");
     window.ORIENTATIONS = ["N", "E", "S", "W"];
     window.current_orientation = 0;
-    window.PLANE_SHAPES = {
-        "N": [[-2,  1], [-1,  1], [ 0,  1], [ 1,  1], [ 2,  1], [ 0,  2], [-1,  3], [ 0,  3], [ 1,  3]],
-        "S": [[ 2, -1], [ 1, -1], [ 0, -1], [-1, -1], [-2, -1], [ 0, -2], [ 1, -3], [ 0, -3], [-1, -3]],
-        "E": [[-1, -2], [-1, -1], [-1,  0], [-1,  1], [-1,  2], [-2,  0], [-3, -1], [-3,  0], [-3,  1]],
-        "W": [[ 1,  2], [ 1,  1], [ 1,  0], [ 1, -1], [ 1, -2], [ 2,  0], [ 3,  1], [ 3,  0], [ 3, -1]],
+    // The body of the plane to place next when flying north, rotated for
+    // the other orientations.
+    window.PLANE_BODY = $("#own_board").data("plane-body") || [];
+    window.rotate_plane_body = function(deltas, orientation) {
+        switch(orientation) {
+            case "S": return [-deltas[0], -deltas[1]];
+            case "E": return [-deltas[1], deltas[0]];
+            case "W": return [deltas[1], -deltas[0]];
+            default: return deltas;
+        }
     }
     window.get_tile_coordinates = function($elem) {
         var this_x = $elem.prevAll("td").length;
@@ -39,8 +44,8 @@ $(document).ready(function() {
     window.get_plane_tiles_from_head = function($grid, $head, orientation) {
         var head_data = window.get_tile_coordinates($head);
         var tiles = [];
-        for(var i = 0; i < window.PLANE_SHAPES[orientation].length; i++) {
-            var deltas = window.PLANE_SHAPES[orientation][i];
+        for(var i = 0; i < window.PLANE_BODY.length; i++) {
+            var deltas = window.rotate_plane_body(window.PLANE_BODY[i], orientation);
             var tile = window.get_tile_by_coord($grid, head_data.x + deltas[0], head_data.y + deltas[1]);
             if(tile != false) {
                 tiles.push(tile);
@@ -71,6 +76,16 @@ $(document).ready(function() {
         }
     }
 
+    // live updates: the server pushes every move, reload to show it
+    var events_url = $("#game").data("events");
+    if (events_url && window.EventSource) {
+        var source = new EventSource(events_url);
+        source.onmessage = function(message) {
+            source.close();
+            window.location.replace(window.location.pathname);
+        };
+    }
+
     // game initialization
     if ($("#new_head").length == 1 && $("#new_orientation").length == 1) {
 
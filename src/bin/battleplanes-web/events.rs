@@ -0,0 +1,164 @@
+//! Live updates of games, pushed to browsers and other clients as server-sent
+//! events.
+//!
+//! Every entry added to the move log of a game is sent as one event whose data
+//! is the `Move` in JSON, except that placements only tell which player placed
+//! a plane, `{"Placed": {"player": 1}}`, so that nobody learns where the
+//! planes of the others are, see `Move::to_public_json`.
+//!
+//! Every stream keeps a worker thread of the server busy for as long as it
+//! is open, so at most `MAX_STREAMS` are open at once; more clients get a
+//! `503` and do without live updates.
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use iron::prelude::*;
+use iron::status;
+use iron::mime::Mime;
+use iron::response::{WriteBody, ResponseBody};
+use router::Router;
+
+use battleplanes;
+use {GamePool, GamePoolMiddleware, SessionId, get_session_id, WORKER_THREADS};
+
+/// How often an idle stream sends a comment, so that proxies and browsers
+/// keep the connection open, and dead connections are noticed.
+const KEEPALIVE_INTERVAL_SECS: u64 = 15;
+/// How many streams may be open at once, leaving most workers to the pages.
+pub const MAX_STREAMS: usize = WORKER_THREADS / 4;
+
+/// The subscribers of every game, keyed like the games in the `GamePool`.
+pub struct EventHub {
+    subscribers: Mutex<HashMap<String, Vec<Sender<String>>>>,
+    /// How many streams are open.
+    streams: Arc<AtomicUsize>,
+}
+
+impl EventHub {
+    pub fn new() -> EventHub {
+        EventHub {
+            subscribers: Mutex::new(HashMap::new()),
+            streams: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+    /// Takes one of the `MAX_STREAMS` slots, given back when the stream
+    /// closes, or `None` when they are all taken.
+    fn open_stream(&self) -> Option<StreamSlot> {
+        let mut open = self.streams.load(Ordering::SeqCst);
+        while open < MAX_STREAMS {
+            match self.streams.compare_exchange(open, open + 1, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => return Some(StreamSlot(self.streams.clone())),
+                Err(current) => open = current,
+            }
+        }
+        None
+    }
+    pub fn subscribe(&self, game_key: &String) -> Receiver<String> {
+        let (sender, receiver) = channel();
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.entry(game_key.clone()).or_insert(Vec::new()).push(sender);
+        receiver
+    }
+    /// Sends the moves to everyone following the game, forgetting the
+    /// subscribers which went away.
+    pub fn publish(&self, game_key: &String, moves: &[battleplanes::Move]) {
+        if moves.is_empty() {
+            return;
+        }
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let mut remove_game = false;
+        if let Some(senders) = subscribers.get_mut(game_key) {
+            for logged in moves {
+                let data = logged.to_public_json().to_string();
+                senders.retain(|sender| sender.send(data.clone()).is_ok());
+            }
+            remove_game = senders.is_empty();
+        }
+        if remove_game {
+            subscribers.remove(game_key);
+        }
+    }
+}
+
+/// An open stream, counted in `EventHub::streams` until dropped.
+struct StreamSlot(Arc<AtomicUsize>);
+
+impl Drop for StreamSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// The body of an event stream response, written until the client leaves.
+struct EventStream(Receiver<String>, StreamSlot);
+
+impl WriteBody for EventStream {
+    fn write_body(&mut self, res: &mut ResponseBody) -> io::Result<()> {
+        res.write_all(b": connected\n\n")?;
+        res.flush()?;
+        loop {
+            let chunk = match self.0.recv_timeout(Duration::from_secs(KEEPALIVE_INTERVAL_SECS)) {
+                Ok(data) => format!("data: {}\n\n", data),
+                Err(RecvTimeoutError::Timeout) => ": keepalive\n\n".to_string(),
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            };
+            res.write_all(chunk.as_bytes())?;
+            res.flush()?;
+        }
+    }
+}
+
+/// Events of the game of the session against the AI.
+pub fn action_events(req: &mut Request) -> IronResult<Response> {
+    let sessionid : SessionId = get_session_id(req);
+    stream_events(req, Some(sessionid.to_string()))
+}
+
+/// Events of the match the session is playing.
+pub fn action_match_events(req: &mut Request) -> IronResult<Response> {
+    let sessionid : SessionId = get_session_id(req);
+    let game_key = {
+        let t = req.get::<GamePoolMiddleware>();
+        let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+        let gamepool = arc.read().ok().unwrap();
+        gamepool.seats.find(&sessionid.to_string()).map(|seat| seat.get().game_key.clone())
+    };
+    stream_events(req, game_key)
+}
+
+/// Events of any game by its id, for API clients and spectators.
+pub fn action_api_events(req: &mut Request) -> IronResult<Response> {
+    let game_key = req.extensions.get::<Router>().unwrap().find("id").map(|id| id.to_string());
+    stream_events(req, game_key)
+}
+
+fn stream_events(req: &mut Request, game_key: Option<String>) -> IronResult<Response> {
+    let t = req.get::<GamePoolMiddleware>();
+    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+    let (receiver, slot) = {
+        let gamepool = arc.read().ok().unwrap();
+        match game_key {
+            Some(ref game_key) if gamepool.store.find_game(game_key).is_some() => {
+                let slot = match gamepool.events.open_stream() {
+                    Some(slot) => slot,
+                    None => {
+                        let mut resp = Response::with((status::ServiceUnavailable, "too many live streams"));
+                        resp.headers.set_raw("Retry-After", vec![KEEPALIVE_INTERVAL_SECS.to_string().into_bytes()]);
+                        return Ok(resp);
+                    },
+                };
+                (gamepool.events.subscribe(game_key), slot)
+            },
+            _ => return Ok(Response::with((status::NotFound, "no such game"))),
+        }
+    };
+    let mime: Mime = "text/event-stream".parse().unwrap();
+    let mut resp = Response::with((status::Ok, mime, Box::new(EventStream(receiver, slot)) as Box<WriteBody>));
+    resp.headers.set_raw("Cache-Control", vec![b"no-cache".to_vec()]);
+    Ok(resp)
+}
@@ -0,0 +1,93 @@
+//! Forgetting the games nobody plays anymore. Every time a game is looked up
+//! or stored, its key is marked as active; a background thread sweeps the
+//! games which stayed idle longer than `GAME_IDLE_TTL_SECS` (an hour by
+//! default) out of the store, together with their AI player, seats and join
+//! code. The stats of a player go the same way once unused for longer than
+//! `STATS_IDLE_TTL_SECS` (30 days by default).
+//!
+//! `GET /status` answers with the counts, e.g.
+//! `{"games": 12, "matches_waiting": 1, "seats": 4, "evicted": 30, "idle_ttl_secs": 3600, "player_stats": 25}`.
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use concurrent_hashmap::ConcHashMap;
+use iron::prelude::*;
+use iron::status;
+use iron::mime::Mime;
+
+use {GamePool, GamePoolMiddleware};
+
+/// How often the sweeper looks for idle games.
+const SWEEP_INTERVAL_SECS: u64 = 60;
+
+impl GamePool {
+    /// Marks the game under `key` as just played.
+    pub fn touch(&self, key: &String) {
+        self.last_activity.insert(key.clone(), Instant::now());
+    }
+    /// Marks the stats of the player under `key` as just used.
+    pub fn touch_stats(&self, key: &String) {
+        self.stats_activity.insert(key.clone(), Instant::now());
+    }
+    /// Removes the games idle for longer than the TTL, and returns how many,
+    /// then the stats unused for longer than theirs.
+    fn sweep(&mut self) -> usize {
+        let idle_games = idle_keys(self.store.game_keys(), &self.last_activity, self.idle_ttl);
+        for key in &idle_games {
+            self.remove_game(key.clone());
+        }
+        self.evicted += idle_games.len();
+        for key in idle_keys(self.store.player_stats_keys(), &self.stats_activity, self.stats_ttl) {
+            self.store.remove_player_stats(&key);
+            self.stats_activity.remove(&key);
+        }
+        idle_games.len()
+    }
+}
+
+/// The `keys` whose last `activity` is older than `ttl`. Keys the server has
+/// not seen yet, e.g. those left in a `FileStore` by a previous run, start
+/// being idle now.
+fn idle_keys(keys: Vec<String>, activity: &ConcHashMap<String, Instant>, ttl: Duration) -> Vec<String> {
+    let now = Instant::now();
+    let mut idle_keys = Vec::new();
+    for key in keys {
+        let last_activity = activity.find(&key).map(|activity| *activity.get());
+        match last_activity {
+            Some(last_activity) if now.duration_since(last_activity) > ttl => idle_keys.push(key),
+            Some(_) => { },
+            None => { activity.insert(key, now); },
+        }
+    }
+    idle_keys
+}
+
+/// Sweeps the pool every `SWEEP_INTERVAL_SECS` for as long as the server runs.
+pub fn spawn_sweeper(gamepool: Arc<RwLock<GamePool>>) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(Duration::from_secs(SWEEP_INTERVAL_SECS));
+            let evicted = gamepool.write().ok().unwrap().sweep();
+            if evicted > 0 {
+                println!("Evicted {} idle games", evicted);
+            }
+        }
+    });
+}
+
+pub fn action_status(req: &mut Request) -> IronResult<Response> {
+    let t = req.get::<GamePoolMiddleware>();
+    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+    let gamepool = arc.read().ok().unwrap();
+    let body = json!({
+        "games": gamepool.store.game_keys().len(),
+        "matches_waiting": gamepool.join_codes.iter().count(),
+        "seats": gamepool.seats.iter().count(),
+        "evicted": gamepool.evicted,
+        "idle_ttl_secs": gamepool.idle_ttl.as_secs(),
+        "player_stats": gamepool.store.player_stats_keys().len(),
+    });
+    let mime: Mime = "application/json".parse().unwrap();
+    Ok(Response::with((status::Ok, mime, body.to_string())))
+}
@@ -0,0 +1,224 @@
+//! Matches between two humans. One session starts a match and gets a join
+//! code to share, a second session joins with that code, and from then on
+//! both play the same `Game` under `/match/`, each seeing only its own board
+//! and scrapbook.
+use std::sync::{Arc, RwLock};
+
+use iron;
+use iron::prelude::*;
+use iron::status;
+use iron_sessionstorage::traits::*;
+use maud;
+use rand;
+use rand::Rng;
+use urlparse;
+use uuid::Uuid;
+
+use battleplanes;
+use {GamePool, GamePoolMiddleware, SessionId, get_rules, get_session_id, queue_submitted_move, rules_to_start_with, template};
+
+const JOIN_CODE_ALPHABET: &'static [u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const JOIN_CODE_LENGTH: usize = 6;
+
+/// The player slot a session occupies in the game of a match.
+#[derive(Clone)]
+pub struct Seat {
+    pub game_key: String,
+    pub player: usize,
+}
+
+impl GamePool {
+    fn find_seat(&self, session: &String) -> Option<Seat> {
+        self.seats.find(session).map(|seat| seat.get().clone())
+    }
+    /// Starts a match with `session` as its first player, and returns the
+    /// code the second player joins with.
+    fn open_match(&mut self, session: String, rules: battleplanes::Rules) -> String {
+        let game_key = Uuid::new_v4().hyphenated().to_string();
+        let game = battleplanes::Game::new_random_starter_with_rules(true, rules_to_start_with(&rules), &mut rand::thread_rng());
+        self.store_game(game_key.clone(), &game);
+        self.seats.insert(session, Seat { game_key: game_key.clone(), player: 0 });
+        let mut code = new_join_code();
+        while self.join_codes.find(&code).is_some() {
+            code = new_join_code();
+        }
+        self.join_codes.insert(code.clone(), game_key);
+        code
+    }
+    /// Seats `session` as the second player of the match waiting with `code`.
+    fn join_match(&mut self, session: String, code: &str) -> Option<Seat> {
+        let code = code.trim().to_uppercase();
+        let game_key = match self.join_codes.remove(&code) {
+            Some(game_key) => game_key,
+            None => return None,
+        };
+        if let Some(seat) = self.find_seat(&session) {
+            if seat.game_key == game_key {
+                // nobody can play against themselves, keep waiting for someone else
+                self.join_codes.insert(code, game_key);
+                return None;
+            }
+        }
+        let seat = Seat { game_key: game_key, player: 1 };
+        self.seats.insert(session, seat.clone());
+        Some(seat)
+    }
+    /// The code of the match, as long as it is waiting for a second player.
+    fn find_join_code(&self, game_key: &String) -> Option<String> {
+        self.join_codes.iter()
+            .find(|&(_, waiting)| waiting == game_key)
+            .map(|(code, _)| code.clone())
+    }
+}
+
+fn new_join_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..JOIN_CODE_LENGTH)
+        .map(|_| *rng.choose(JOIN_CODE_ALPHABET).unwrap() as char)
+        .collect()
+}
+
+pub fn action_match(req: &mut Request) -> IronResult<Response> {
+    render_match(req, false)
+}
+
+pub fn action_match_hint(req: &mut Request) -> IronResult<Response> {
+    render_match(req, true)
+}
+
+pub fn action_match_new(req: &mut Request) -> IronResult<Response> {
+    let sessionid : SessionId = get_session_id(req);
+    let t = req.get::<GamePoolMiddleware>();
+    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+    let mut gamepool = arc.write().ok().unwrap();
+
+    let code = gamepool.open_match(sessionid.to_string(), get_rules(req));
+    println!("Session {} opened match {}", sessionid.to_string(), code);
+    try!(req.session().set(sessionid));
+    Ok(redirect_to_match())
+}
+
+pub fn action_match_join(req: &mut Request) -> IronResult<Response> {
+    let sessionid : SessionId = get_session_id(req);
+    let t = req.get::<GamePoolMiddleware>();
+    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+    let mut gamepool = arc.write().ok().unwrap();
+
+    let code = match req.url.query() {
+        Some(query) => urlparse::parse_qs(query).get(&"code".to_string())
+            .and_then(|codes| codes.get(0))
+            .cloned()
+            .unwrap_or("".to_string()),
+        None => "".to_string(),
+    };
+    let seat = gamepool.join_match(sessionid.to_string(), code.as_str());
+    try!(req.session().set(sessionid));
+    match seat {
+        Some(_) => Ok(redirect_to_match()),
+        None => {
+            let error = format!("No match is waiting for a player with the code {}.", code);
+            Ok(Response::with((status::Ok, template::with_layout(lobby_as_html(Some(error))))))
+        },
+    }
+}
+
+/// The match of the session seen by its player, who may submit a move, or
+/// the lobby if the session is not in a match.
+fn render_match(req: &mut Request, with_hint: bool) -> IronResult<Response> {
+    let sessionid : SessionId = get_session_id(req);
+    let t = req.get::<GamePoolMiddleware>();
+    let arc : Arc<RwLock<GamePool>> = t.ok().unwrap();
+    let mut resp = Response::new();
+
+    // The pool is only locked to fetch and store the game, the heatmap may
+    // take a while to work out.
+    let (events, seat, join_code, mut game) = {
+        let mut gamepool = arc.write().ok().unwrap();
+        let seat = gamepool.find_seat(&sessionid.to_string());
+        let (seat, join_code) = match seat {
+            Some(seat) => {
+                let join_code = gamepool.find_join_code(&seat.game_key);
+                (seat, join_code)
+            },
+            None => {
+                try!(req.session().set(sessionid));
+                resp.set_mut(template::with_layout(lobby_as_html(None)));
+                return Ok(resp);
+            },
+        };
+        let game = match gamepool.find_existing_game(&seat.game_key) {
+            Some(game) => game,
+            None => {
+                try!(req.session().set(sessionid));
+                resp.set_mut(template::with_layout(lobby_as_html(Some("Your match is over.".to_string()))));
+                return Ok(resp);
+            },
+        };
+        (gamepool.events.clone(), seat, join_code, game)
+    };
+    let me = seat.player;
+
+    let heatmap = match with_hint {
+        true if game.gameplay == battleplanes::GamePlay::Bombard(me) => Some(battleplanes::Heatmap::new(&game.players[me].scrapbook)),
+        true => return Ok(redirect_to_match()),
+        false => None,
+    };
+    if !with_hint {
+        let mut players = vec![battleplanes::QueuedPlayer::new(), battleplanes::QueuedPlayer::new()];
+        if queue_submitted_move(req, &game.gameplay, me, &mut players[me]) {
+            resp = redirect_to_match();
+        }
+        let players: Vec<Box<battleplanes::Player>> = players.into_iter()
+            .map(|player| Box::new(player) as Box<battleplanes::Player>)
+            .collect();
+        let moves_before = game.moves.len();
+        battleplanes::Engine::new(&mut game, players).run(|_, event| println!("{:?}", event));
+        if game.moves.len() > moves_before {
+            let stored = arc.write().ok().unwrap().store_game_if_unchanged(seat.game_key.clone(), moves_before, &game);
+            if !stored {
+                // The opponent played first, the page shows where it got to.
+                return Ok(redirect_to_match());
+            }
+            events.publish(&seat.game_key, &game.moves[moves_before..]);
+        }
+    }
+
+    let boards_markup = template::player_boards_as_html(&game.players[me].board, &game.players[me].scrapbook, &game.gameplay, me, "/match/", heatmap.as_ref());
+    let match_markup = html! {
+        @if let Some(ref code) = join_code {
+            p.centered {
+                "Waiting for your opponent, who can join with the code "
+                strong { (code) }
+                "."
+            }
+        }
+        (template::live(boards_markup, "/match/events"))
+    };
+    try!(req.session().set(sessionid));
+    resp.set_mut(template::with_layout(match_markup));
+    Ok(resp)
+}
+
+fn redirect_to_match() -> Response {
+    let mut resp = Response::new();
+    resp.headers.set(iron::headers::Location("/match/".to_string()));
+    resp.set_mut(status::Found);
+    resp
+}
+
+fn lobby_as_html(error: Option<String>) -> maud::Markup {
+    html! {
+        div.centered {
+            @if let Some(ref error) = error {
+                p { (error) }
+            }
+            p {
+                a href="/match/new" { "Start a match against a friend" }
+            }
+            form action="/match/join" {
+                input name="code" placeholder="Join code" /
+                input type="submit" value="Join a match" /
+            }
+        }
+    }
+}
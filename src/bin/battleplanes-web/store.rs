@@ -0,0 +1,214 @@
+//! Where the `GamePool` keeps the games and the AI players, keyed by session
+//! id, match or API id, and what the AI learned about each session over its
+//! past games, until the sweeper of `expiry` forgets it.
+//!
+//! The store is picked with the `GAME_STORE` environment variable:
+//! `memory`, the default, loses everything when the server stops, while
+//! `file` keeps every game as JSON in the directory `GAME_STORE_PATH`
+//! (`games` by default), so that a restart does not kick out the players.
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use concurrent_hashmap::ConcHashMap;
+use serde_json;
+use serde_json::Value;
+
+use battleplanes;
+
+pub trait GameStore: Send + Sync {
+    fn find_game(&self, key: &String) -> Option<battleplanes::Game>;
+    fn save_game(&self, key: &String, game: &battleplanes::Game);
+    fn find_ai_player(&self, key: &String) -> Option<battleplanes::AiPlayer>;
+    fn save_ai_player(&self, key: &String, ai: &battleplanes::AiPlayer);
+    fn find_player_stats(&self, key: &String) -> Option<battleplanes::PlayerStats>;
+    fn save_player_stats(&self, key: &String, stats: &battleplanes::PlayerStats);
+    /// Forgets the game and the AI player under `key`, but not the stats of
+    /// the player, which outlive their games.
+    fn remove(&self, key: &String);
+    /// The keys of all the games.
+    fn game_keys(&self) -> Vec<String>;
+    /// Forgets the stats of the player under `key`.
+    fn remove_player_stats(&self, key: &String);
+    /// The keys of all the players with stats.
+    fn player_stats_keys(&self) -> Vec<String>;
+}
+
+pub fn new_game_store(kind: &str, path: &str) -> Result<Box<GameStore>, String> {
+    match kind {
+        "memory" => Ok(Box::new(MemoryStore::new())),
+        "file" => FileStore::new(path).map(|store| Box::new(store) as Box<GameStore>)
+            .map_err(|error| format!("cannot use {} for the game store: {}", path, error)),
+        _ => Err(format!("unknown game store {}, use memory or file", kind)),
+    }
+}
+
+pub struct MemoryStore {
+    games: ConcHashMap<String, battleplanes::Game>,
+    ai_players: ConcHashMap<String, battleplanes::AiPlayer>,
+    player_stats: ConcHashMap<String, battleplanes::PlayerStats>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore {
+            games: ConcHashMap::<String, battleplanes::Game>::new(),
+            ai_players: ConcHashMap::<String, battleplanes::AiPlayer>::new(),
+            player_stats: ConcHashMap::<String, battleplanes::PlayerStats>::new(),
+        }
+    }
+}
+
+impl GameStore for MemoryStore {
+    fn find_game(&self, key: &String) -> Option<battleplanes::Game> {
+        self.games.find(key).map(|game| game.get().clone())
+    }
+    fn save_game(&self, key: &String, game: &battleplanes::Game) {
+        self.games.insert(key.clone(), game.clone());
+    }
+    fn find_ai_player(&self, key: &String) -> Option<battleplanes::AiPlayer> {
+        self.ai_players.find(key).map(|ai| ai.get().clone())
+    }
+    fn save_ai_player(&self, key: &String, ai: &battleplanes::AiPlayer) {
+        self.ai_players.insert(key.clone(), ai.clone());
+    }
+    fn find_player_stats(&self, key: &String) -> Option<battleplanes::PlayerStats> {
+        self.player_stats.find(key).map(|stats| stats.get().clone())
+    }
+    fn save_player_stats(&self, key: &String, stats: &battleplanes::PlayerStats) {
+        self.player_stats.insert(key.clone(), stats.clone());
+    }
+    fn remove(&self, key: &String) {
+        self.games.remove(key);
+        self.ai_players.remove(key);
+    }
+    fn game_keys(&self) -> Vec<String> {
+        self.games.iter().map(|(key, _)| key.clone()).collect()
+    }
+    fn remove_player_stats(&self, key: &String) {
+        self.player_stats.remove(key);
+    }
+    fn player_stats_keys(&self) -> Vec<String> {
+        self.player_stats.iter().map(|(key, _)| key.clone()).collect()
+    }
+}
+
+/// One `{key}.game.json` and one `{key}.ai.json` per game, and one
+/// `{key}.stats.json` per player. Files which cannot be read back are logged
+/// and treated as missing.
+pub struct FileStore {
+    directory: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: &str) -> io::Result<FileStore> {
+        fs::create_dir_all(path)?;
+        Ok(FileStore {
+            directory: PathBuf::from(path),
+        })
+    }
+    /// Keys come from cookies and URLs, only plain ones may become file names.
+    fn path_of(&self, key: &String, suffix: &str) -> Option<PathBuf> {
+        let is_plain = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        match is_plain {
+            true => Some(self.directory.join(format!("{}.{}.json", key, suffix))),
+            false => None,
+        }
+    }
+    fn read(&self, key: &String, suffix: &str) -> Option<Value> {
+        let path = match self.path_of(key, suffix) {
+            Some(path) => path,
+            None => return None,
+        };
+        let mut json = String::new();
+        if File::open(&path).and_then(|mut file| file.read_to_string(&mut json)).is_err() {
+            return None;
+        }
+        match serde_json::from_str(&json) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                println!("Error in {} on {}: cannot read {}: {}", file!(), line!(), path.display(), error);
+                None
+            },
+        }
+    }
+    /// The keys of all the files ending in `.{suffix}.json`.
+    fn keys(&self, suffix: &str) -> Vec<String> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        let ending = format!(".{}.json", suffix);
+        entries.filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.ends_with(&ending))
+            .map(|name| name.trim_right_matches(ending.as_str()).to_string())
+            .collect()
+    }
+    /// Writes next to the target first, so that a crash never leaves half a
+    /// game behind.
+    fn write(&self, key: &String, suffix: &str, value: &Value) {
+        let path = match self.path_of(key, suffix) {
+            Some(path) => path,
+            None => return,
+        };
+        let temporary = path.with_extension("json.tmp");
+        let written = File::create(&temporary)
+            .and_then(|mut file| file.write_all(value.to_string().as_bytes()))
+            .and_then(|_| fs::rename(&temporary, &path));
+        if let Err(error) = written {
+            println!("Error in {} on {}: cannot write {}: {}", file!(), line!(), path.display(), error);
+        }
+    }
+}
+
+impl GameStore for FileStore {
+    fn find_game(&self, key: &String) -> Option<battleplanes::Game> {
+        self.read(key, "game").and_then(|value| serde_json::from_value(value).ok())
+    }
+    fn save_game(&self, key: &String, game: &battleplanes::Game) {
+        self.write(key, "game", &serde_json::to_value(game).unwrap());
+    }
+    fn find_ai_player(&self, key: &String) -> Option<battleplanes::AiPlayer> {
+        self.read(key, "ai").and_then(|value| {
+            let level = serde_json::from_value(value["level"].clone()).ok();
+            let layout = serde_json::from_value(value["layout"].clone()).ok()
+                .filter(|layout: &battleplanes::Board| layout.validate().is_ok());
+            // Absent from the files written before AIs learned.
+            let history = serde_json::from_value(value["history"].clone()).unwrap_or_default();
+            match (level, layout) {
+                (Some(level), Some(layout)) => Some(battleplanes::AiPlayer::new_with_history(layout, level, history)),
+                _ => None,
+            }
+        })
+    }
+    fn save_ai_player(&self, key: &String, ai: &battleplanes::AiPlayer) {
+        self.write(key, "ai", &json!({ "level": ai.level(), "layout": ai.layout(), "history": ai.history() }));
+    }
+    fn find_player_stats(&self, key: &String) -> Option<battleplanes::PlayerStats> {
+        self.read(key, "stats").and_then(|value| serde_json::from_value(value).ok())
+    }
+    fn save_player_stats(&self, key: &String, stats: &battleplanes::PlayerStats) {
+        self.write(key, "stats", &serde_json::to_value(stats).unwrap());
+    }
+    fn remove(&self, key: &String) {
+        for suffix in &["game", "ai"] {
+            if let Some(path) = self.path_of(key, suffix) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+    fn game_keys(&self) -> Vec<String> {
+        self.keys("game")
+    }
+    fn remove_player_stats(&self, key: &String) {
+        if let Some(path) = self.path_of(key, "stats") {
+            let _ = fs::remove_file(path);
+        }
+    }
+    fn player_stats_keys(&self) -> Vec<String> {
+        self.keys("stats")
+    }
+}
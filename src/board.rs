@@ -5,11 +5,25 @@ use rand;
 use rand::Rng;
 
 use plane::Plane;
+use plane_shape::PlaneShape;
+use fleet::Fleet;
 use coordinate::Coordinate;
 use orientation::Orientation;
+use board_spec::BoardSpec;
+use rules::DEFAULT_FLEET_SIZE;
 use bombardment_result::BombardmentResult;
+use placement_error::PlacementError;
+use layout_error::LayoutError;
+use bombardment_strategy::all_placements;
+
+/// How many times a random layout is started over before it is searched
+/// for instead.
+const MAX_RANDOM_ATTEMPTS: usize = 10;
+/// How many planes a search for a layout may try before giving up.
+const MAX_LAYOUT_SEARCH_STEPS: usize = 1_000_000;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Board {
     planes: Vec<Plane>,
     killed_planes: Vec<Plane>,
@@ -17,7 +31,16 @@ pub struct Board {
     misses: Vec<Coordinate>,
     kills: Vec<Coordinate>,
     empty_indices: BTreeSet<usize>,
-    previous_error_message: Option<String>,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    previous_error: Option<PlacementError>,
+    /// Boards saved before maps could be resized are 10x10.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    spec: BoardSpec,
+    /// The planes which go on the board, in order; on a scrapbook, those the
+    /// opponent has. Boards saved before fleets could change have three
+    /// classic planes.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    fleet: Fleet,
 }
 
 impl Board {
@@ -28,8 +51,20 @@ impl Board {
         &mut self.empty_indices
     }
     pub fn new() -> Board {
+        Board::new_with_spec(BoardSpec::default())
+    }
+    pub fn new_with_spec(spec: BoardSpec) -> Board {
+        Board::new_with_fleet(spec, DEFAULT_FLEET_SIZE)
+    }
+    pub fn new_with_fleet(spec: BoardSpec, fleet_size: usize) -> Board {
+        Board::new_with_shape(spec, fleet_size, PlaneShape::default())
+    }
+    pub fn new_with_shape(spec: BoardSpec, fleet_size: usize, shape: PlaneShape) -> Board {
+        Board::new_with_mixed_fleet(spec, Fleet::new_uniform(fleet_size, shape))
+    }
+    pub fn new_with_mixed_fleet(spec: BoardSpec, fleet: Fleet) -> Board {
         let mut empty_indices: BTreeSet<usize> = BTreeSet::new();
-        for i in 0..100 {
+        for i in 0..spec.tile_count() {
             empty_indices.insert(i);
         }
         Board {
@@ -39,24 +74,65 @@ impl Board {
             misses: Vec::new(),
             kills: Vec::new(),
             empty_indices: empty_indices,
-            previous_error_message: None,
+            previous_error: None,
+            spec: spec,
+            fleet: fleet,
         }
     }
+    pub fn spec(&self) -> &BoardSpec {
+        &self.spec
+    }
+    pub fn fleet_size(&self) -> usize {
+        self.fleet.len()
+    }
+    pub fn fleet(&self) -> &Fleet {
+        &self.fleet
+    }
+    /// The shape of the plane to place next, `None` once all are placed.
+    pub fn next_shape(&self) -> Option<&PlaneShape> {
+        self.fleet.get(self.planes.len() + self.killed_planes.len())
+    }
     pub fn get_previous_hit_message(&self) -> String {
-        match self.previous_error_message {
-            Some(ref msg) => msg.clone(),
+        match self.previous_error {
+            Some(ref error) => format!("{}", error),
             None => "".to_string(),
         }
     }
+    pub fn previous_error(&self) -> Option<&PlacementError> {
+        self.previous_error.as_ref()
+    }
     pub fn new_random() -> Board {
-        let mut temp_board = Board::new();
+        Board::new_random_with_rng(&mut rand::thread_rng())
+    }
+    pub fn new_random_with_rng<R: Rng>(rng: &mut R) -> Board {
+        Board::new_random_with_spec(&BoardSpec::default(), rng)
+    }
+    pub fn new_random_with_spec<R: Rng>(spec: &BoardSpec, rng: &mut R) -> Board {
+        Board::new_random_with_fleet(spec, DEFAULT_FLEET_SIZE, rng)
+    }
+    pub fn new_random_with_fleet<R: Rng>(spec: &BoardSpec, fleet_size: usize, rng: &mut R) -> Board {
+        Board::new_random_with_shape(spec, fleet_size, PlaneShape::default(), rng)
+    }
+    pub fn new_random_with_shape<R: Rng>(spec: &BoardSpec, fleet_size: usize, shape: PlaneShape, rng: &mut R) -> Board {
+        Board::new_random_with_mixed_fleet(spec, Fleet::new_uniform(fleet_size, shape), rng)
+    }
+    /// A uniformly random layout; `PlacementStrategy` has smarter ones.
+    ///
+    /// Crowded maps may take too many attempts, in which case the layout is
+    /// searched for, first in a random order, then in the order of
+    /// `new_first_fit_with_mixed_fleet`, which always finds one for the
+    /// fleets `Rules::can_be_laid_out` accepts.
+    ///
+    /// # Panics
+    ///
+    /// When the fleet does not fit on the map.
+    pub fn new_random_with_mixed_fleet<R: Rng>(spec: &BoardSpec, fleet: Fleet, rng: &mut R) -> Board {
+        let mut temp_board = Board::new_with_mixed_fleet(*spec, fleet.clone());
 
-        let mut rng = rand::thread_rng();
         let mut random_orientations = [0 as usize, 1, 2, 3];
-        //TODO: better heuristics, AI levels
-        loop {
+        for _ in 0..MAX_RANDOM_ATTEMPTS {
             for _ in 0..100 {
-                let head_candidate = Coordinate::new_random_coordinate();
+                let head_candidate = Coordinate::new_random_coordinate_with_rng(spec, rng);
                 let raw_head_candidate = format!("{}", head_candidate);
 
                 rng.shuffle(&mut random_orientations);
@@ -72,69 +148,167 @@ impl Board {
                         }
                     }
                 }
-                if temp_board.planes.len() == 3 {
-                    break;
+                if temp_board.is_initialized() {
+                    return temp_board;
                 }
             }
-            if temp_board.planes.len() == 3 {
-                break;
-            } else {
-                temp_board.clear_planes();
-            }
+            temp_board.clear_planes();
+        }
+        let shuffled = Board::search_layout(spec, &fleet, |candidates| rng.shuffle(&mut candidates[..]));
+        match shuffled.or_else(|| Board::new_first_fit_with_mixed_fleet(spec, fleet.clone())) {
+            Some(layout) => layout,
+            None => panic!("a fleet of {} planes does not fit on a {} map", fleet.len(), spec),
         }
-        temp_board
     }
-
-    pub fn add_new_plane_at(&mut self, head: &str, orientation: &str) -> Result<usize, String> {
-        if self.is_in_gameplay() {
-            let t = "Cannot add planes mid-game".to_string();
-            self.previous_error_message = Some(t.clone());
-            return Err(t);
-        }
-        let t_plane = Plane::new_with_id(head, orientation, self.planes.len()+1);
-        match t_plane {
-            None => {
-                let t = format!("plane cannot spawn at {} in direction {}", head, orientation);
-                self.previous_error_message = Some(t.clone());
-                Err(t)
-            },
-            Some(plane) => {
-                match plane.is_outside_of_map() {
-                    true => {
-                        let t = "Plane would fall off the map, try again.".to_string();
-                        self.previous_error_message = Some(t.clone());
-                        Err(t)
-                    },
-                    false => {
-                        // TODO: return list of overlapping other planes in error message
-                        for other in &self.planes {
-                            if plane.is_overlapping_with(&other) {
-                                let t = format!("Plane would overlap with another one: {}, try again.", other.id());
-                                self.previous_error_message = Some(t.clone());
-                                return Err(t);
-                            }
-                        }
-                        let head_offset = plane.head().as_usize();
-                        self.empty_indices.remove(&head_offset);
-                        for tile in plane.tile_iterator() {
-                            self.empty_indices.remove(&tile);
+    /// The first layout found by placing the planes in order, each one as
+    /// close to the top left corner as it fits, and moving the previous ones
+    /// on when the next one does not fit anywhere. `None` when there is no
+    /// layout, or when the search takes too long.
+    pub fn new_first_fit_with_mixed_fleet(spec: &BoardSpec, fleet: Fleet) -> Option<Board> {
+        Board::search_layout(spec, &fleet, |_| { })
+    }
+    /// Depth-first search of a layout, trying the placements of every plane
+    /// in the order `arrange` puts them in.
+    fn search_layout<F: FnMut(&mut Vec<Plane>)>(spec: &BoardSpec, fleet: &Fleet, mut arrange: F) -> Option<Board> {
+        let candidates: Vec<Vec<(Plane, Vec<usize>)>> = fleet.shapes().iter().map(|shape| {
+            let mut placements = all_placements(spec, shape);
+            arrange(&mut placements);
+            placements.into_iter().map(|plane| {
+                let mut tiles: Vec<usize> = plane.tile_iterator(spec).collect();
+                tiles.push(plane.head().as_usize(spec));
+                (plane, tiles)
+            }).collect()
+        }).collect();
+        let mut occupied = vec![false; spec.tile_count()];
+        let mut chosen: Vec<usize> = Vec::with_capacity(candidates.len());
+        let mut next = 0;
+        let mut steps = 0;
+        while chosen.len() < candidates.len() {
+            let plane = chosen.len();
+            let found = candidates[plane][next..].iter().position(|&(_, ref tiles)| {
+                steps += 1;
+                !tiles.iter().any(|&tile| occupied[tile])
+            });
+            if steps > MAX_LAYOUT_SEARCH_STEPS {
+                return None;
+            }
+            match found {
+                Some(offset) => {
+                    let index = next + offset;
+                    for &tile in &candidates[plane][index].1 {
+                        occupied[tile] = true;
+                    }
+                    chosen.push(index);
+                    next = 0;
+                },
+                None => match chosen.pop() {
+                    Some(index) => {
+                        for &tile in &candidates[plane - 1][index].1 {
+                            occupied[tile] = false;
                         }
-                        self.planes.push(plane);
-                        self.previous_error_message = None;
-                        Ok(self.planes.last().unwrap().id())
+                        next = index + 1;
                     },
-                }
+                    None => return None,
+                },
+            }
+        }
+        let mut layout = Board::new_with_mixed_fleet(*spec, fleet.clone());
+        for (plane, &index) in chosen.iter().enumerate() {
+            let candidate = &candidates[plane][index].0;
+            layout.add_new_plane(*candidate.head(), candidate.orientation().clone()).expect("the planes of a searched layout fit together");
+        }
+        Some(layout)
+    }
+    /// A board of three classic planes on a 10x10 map, read from the notation
+    /// `Display` prints, see `from_layout_str_with_fleet`.
+    pub fn from_layout_str(layout: &str) -> Result<Board, Vec<LayoutError>> {
+        Board::from_layout_str_with_fleet(BoardSpec::default(), Fleet::default(), layout)
+    }
+    /// A board with the planes of a layout like `"E5N B2W H8S"`, each one a
+    /// head followed by an orientation, placed in the order of the `fleet`.
+    /// Every plane which does not fit is reported, as well as the planes
+    /// missing from the fleet.
+    pub fn from_layout_str_with_fleet(spec: BoardSpec, fleet: Fleet, layout: &str) -> Result<Board, Vec<LayoutError>> {
+        let mut board = Board::new_with_mixed_fleet(spec, fleet);
+        let mut errors = Vec::new();
+        let mut positions = Vec::new();
+        for (index, plane) in layout.split_whitespace().enumerate() {
+            let split = plane.char_indices().last().map(|(i, _)| i).unwrap_or(0);
+            let (head, orientation) = plane.split_at(split);
+            match board.add_new_plane_at(head, orientation) {
+                Ok(_) => positions.push(index + 1),
+                Err(error) => {
+                    let error = match error {
+                        PlacementError::Overlap { with } => PlacementError::Overlap { with: with.iter().map(|id| positions[id - 1]).collect() },
+                        error => error,
+                    };
+                    errors.push(LayoutError::Plane { position: index + 1, plane: plane.to_string(), error: error });
+                },
             }
         }
+        let placed = board.planes().len();
+        if placed < board.fleet_size() {
+            errors.push(LayoutError::MissingPlanes(board.fleet_size() - placed));
+        }
+        board.previous_error = None;
+        match errors.is_empty() {
+            true => Ok(board),
+            false => Err(errors),
+        }
+    }
+
+    pub fn add_new_plane_at(&mut self, head: &str, orientation: &str) -> Result<usize, PlacementError> {
+        let result = match (Coordinate::new(head).filter(|coord| self.spec.contains(coord)), Orientation::new(orientation)) {
+            (None, _) => Err(PlacementError::InvalidCoordinate(head.to_string())),
+            (_, None) => Err(PlacementError::InvalidOrientation(orientation.to_string())),
+            (Some(head), Some(orientation)) => return self.add_new_plane(head, orientation),
+        };
+        self.previous_error = result.clone().err();
+        result
+    }
+    pub fn add_new_plane(&mut self, head: Coordinate, orientation: Orientation) -> Result<usize, PlacementError> {
+        let result = self.try_add_new_plane(head, orientation);
+        self.previous_error = result.clone().err();
+        result
+    }
+    fn try_add_new_plane(&mut self, head: Coordinate, orientation: Orientation) -> Result<usize, PlacementError> {
+        if self.is_in_gameplay() {
+            return Err(PlacementError::GameAlreadyStarted);
+        }
+        let shape = match self.next_shape() {
+            Some(shape) => shape.clone(),
+            None => return Err(PlacementError::TooManyPlanes),
+        };
+        let plane = Plane::new_with_shape(head, orientation, shape, self.planes.len()+1);
+        if plane.is_outside_of_map(&self.spec) {
+            return Err(PlacementError::OutOfMap);
+        }
+        let overlapping: Vec<usize> = self.planes.iter()
+            .filter(|other| plane.is_overlapping_with(other))
+            .map(|other| other.id())
+            .collect();
+        if !overlapping.is_empty() {
+            return Err(PlacementError::Overlap { with: overlapping });
+        }
+        let head_offset = plane.head().as_usize(&self.spec);
+        self.empty_indices.remove(&head_offset);
+        for tile in plane.tile_iterator(&self.spec) {
+            self.empty_indices.remove(&tile);
+        }
+        self.planes.push(plane);
+        Ok(self.planes.last().unwrap().id())
     }
     fn is_in_gameplay(&self) -> bool {
         !self.hits.is_empty() || !self.misses.is_empty()
     }
     pub fn is_initialized(&self) -> bool {
-        self.planes.len() + self.killed_planes.len() == 3
+        self.planes.len() + self.killed_planes.len() == self.fleet.len()
     }
     pub fn hit_at(&mut self, coord: Coordinate) -> BombardmentResult {
-        self.empty_indices.remove(&coord.as_usize());
+        if !self.spec.contains(&coord) {
+            return BombardmentResult::Retry;
+        }
+        self.empty_indices.remove(&coord.as_usize(&self.spec));
         for i in 0..self.planes.len() {
             if self.planes[i].has_tile(&coord) {
                 self.hits.push(coord);
@@ -142,8 +316,9 @@ impl Board {
             }
             if self.planes[i].head() == &coord {
                 let killed_plane = self.planes.remove(i);
+                let shape_name = killed_plane.shape().name().to_string();
                 self.killed_planes.push(killed_plane);
-                return BombardmentResult::Kill;
+                return BombardmentResult::Kill(shape_name);
             }
         }
         self.misses.push(coord);
@@ -176,6 +351,24 @@ impl Board {
     pub fn kills_mut(&mut self) -> &mut Vec<Coordinate> {
         &mut self.kills
     }
+    /// Checks a board read back from a file or a client: its planes, shots
+    /// and untried tiles all have to be on its map.
+    pub fn validate(&self) -> Result<(), String> {
+        let spec = &self.spec;
+        for plane in self.planes.iter().chain(self.killed_planes.iter()) {
+            if !spec.contains(plane.head()) || plane.coordinate_iterator_on(spec).any(|tile| tile.is_none()) {
+                return Err(format!("the plane at {} is not on the {} map", plane.head(), spec));
+            }
+        }
+        let mut shots = self.hits.iter().chain(self.misses.iter()).chain(self.kills.iter());
+        if let Some(shot) = shots.find(|shot| !spec.contains(shot)) {
+            return Err(format!("the shot at {} is not on the {} map", shot, spec));
+        }
+        if let Some(index) = self.empty_indices.iter().find(|&&index| index >= spec.tile_count()) {
+            return Err(format!("tile {} is not on the {} map", index, spec));
+        }
+        Ok(())
+    }
     pub fn clear_planes(&mut self) {
         self.planes = Vec::new();
     }
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use coordinate::Coordinate;
+
+/// The widest and tallest a map can be, columns being lettered `A` to `Z`.
+pub const MAX_SIZE: usize = 26;
+/// The smallest map a fleet of three planes still fits on.
+pub const MIN_SIZE: usize = 8;
+
+/// The dimensions of the map a game is played on. Columns are lettered from
+/// `A` and rows numbered from `1`; tiles are indexed row by row, so the tile
+/// at column `x` and row `y` is `y * width + x`. Read back from JSON through
+/// `new`, see `serialize.rs`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct BoardSpec {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl BoardSpec {
+    pub fn new(width: usize, height: usize) -> Option<BoardSpec> {
+        let fits = |size: usize| size >= MIN_SIZE && size <= MAX_SIZE;
+        match fits(width) && fits(height) {
+            true => Some(BoardSpec { width: width, height: height }),
+            false => None,
+        }
+    }
+    /// Reads a size like `8x8` or `15x12`, width first.
+    pub fn new_from_str(from: &str) -> Option<BoardSpec> {
+        let mut sides = from.splitn(2, 'x');
+        match (sides.next().map(str::parse), sides.next().map(str::parse)) {
+            (Some(Ok(width)), Some(Ok(height))) => BoardSpec::new(width, height),
+            _ => None,
+        }
+    }
+    pub fn tile_count(&self) -> usize {
+        self.width * self.height
+    }
+    pub fn contains(&self, coord: &Coordinate) -> bool {
+        let (x, y) = coord.as_tuple();
+        x < self.width && y < self.height
+    }
+}
+
+impl Default for BoardSpec {
+    fn default() -> BoardSpec {
+        BoardSpec { width: 10, height: 10 }
+    }
+}
+
+impl fmt::Display for BoardSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
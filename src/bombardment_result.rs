@@ -1,7 +1,13 @@
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum BombardmentResult {
     Hit,
     Miss,
-    Kill,
+    /// The head was hit, which brings down the whole plane, of the shape
+    /// named here.
+    Kill(String),
     Retry,
 }
 
@@ -12,9 +18,8 @@ impl Into<bool> for BombardmentResult {
             Retry => false,
             Miss => false,
             Hit => true,
-            Kill => true,
+            Kill(_) => true,
         }
     }
 }
 
-
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand;
+use rand::Rng;
+
+use board::Board;
+use board_spec::BoardSpec;
+use plane::Plane;
+use plane_shape::PlaneShape;
+use coordinate::Coordinate;
+use orientation::Orientation;
+use heatmap::Heatmap;
+use layout_history::LayoutHistory;
+
+/// How an AI picks the next tile to bombard.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "snake_case"))]
+pub enum BombardmentStrategy {
+    /// Any tile which has not been bombarded yet.
+    Random,
+    /// Hunts randomly until something is hit, then aims at the most likely
+    /// head of the wounded plane until it is killed.
+    HuntAndTarget,
+    /// Works out the `Heatmap` of the scrapbook: hunts on the tile most likely
+    /// to hold any part of a plane, and once something is hit, aims at the
+    /// most likely head. Plays like `HuntAndTarget` while the heatmap is too
+    /// costly to work out.
+    Density,
+    /// Looks at the opponent's board and goes straight for the heads. Only
+    /// meant for testing.
+    Cheat,
+    /// Hunts where the opponent put its planes in past games, as long as
+    /// the scrapbook does not rule those tiles out, then plays like
+    /// `Density`.
+    Adaptive(LayoutHistory),
+}
+
+impl BombardmentStrategy {
+    pub fn new(from: &str) -> Option<BombardmentStrategy> {
+        use BombardmentStrategy::*;
+        match from {
+            "random" => Some(Random),
+            "hunt_and_target" => Some(HuntAndTarget),
+            "density" => Some(Density),
+            "cheat" => Some(Cheat),
+            "adaptive" => Some(Adaptive(LayoutHistory::default())),
+            _ => None,
+        }
+    }
+    /// Picks the next tile to bombard. `opponent_board` is only ever looked
+    /// at when cheating.
+    pub fn next_target(&self, scrapbook: &Board, opponent_board: &Board) -> Option<Coordinate> {
+        self.next_target_with_rng(scrapbook, opponent_board, &mut rand::thread_rng())
+    }
+    /// Same as `next_target`, with ties and hunting shots decided by `rng`.
+    pub fn next_target_with_rng<R: Rng>(&self, scrapbook: &Board, opponent_board: &Board, rng: &mut R) -> Option<Coordinate> {
+        use BombardmentStrategy::*;
+        let target = match self {
+            &Random => None,
+            &HuntAndTarget => most_likely_head(scrapbook, rng),
+            &Density => most_probable_tile(scrapbook, rng).or_else(|| most_likely_head(scrapbook, rng)),
+            &Cheat => {
+                opponent_board.planes().iter()
+                    .map(|plane| *plane.head())
+                    .find(|head| scrapbook.empty_indices().contains(&head.as_usize(scrapbook.spec())))
+                    .or_else(|| most_likely_head(scrapbook, rng))
+            },
+            &Adaptive(ref history) => return BombardmentStrategy::next_adaptive_target_with_rng(history, scrapbook, rng),
+        };
+        target.or_else(|| random_target(scrapbook, rng))
+    }
+    /// What `Adaptive` picks, remembering the layouts of past games in
+    /// `history`, without having to copy it into a strategy for every shot.
+    pub fn next_adaptive_target_with_rng<R: Rng>(history: &LayoutHistory, scrapbook: &Board, rng: &mut R) -> Option<Coordinate> {
+        remembered_tile(scrapbook, history, rng)
+            .or_else(|| most_probable_tile(scrapbook, rng))
+            .or_else(|| most_likely_head(scrapbook, rng))
+            .or_else(|| random_target(scrapbook, rng))
+    }
+}
+
+impl fmt::Display for BombardmentStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use BombardmentStrategy::*;
+        write!(f, "{}", match self {
+            &Random => "random",
+            &HuntAndTarget => "hunt_and_target",
+            &Density => "density",
+            &Cheat => "cheat",
+            &Adaptive(_) => "adaptive",
+        })
+    }
+}
+
+fn random_target<R: Rng>(scrapbook: &Board, rng: &mut R) -> Option<Coordinate> {
+    let empty_indices = scrapbook.empty_indices();
+    if empty_indices.is_empty() {
+        return None;
+    }
+    let wanted : usize = rng.gen::<usize>() % empty_indices.len();
+    empty_indices.iter().nth(wanted).map(|tile_num| Coordinate::new_from_usize(*tile_num, scrapbook.spec()))
+}
+
+/// Every plane of the `shape` which fits on the map, regardless of what is
+/// on the board.
+pub fn all_placements(spec: &BoardSpec, shape: &PlaneShape) -> Vec<Plane> {
+    let mut placements = Vec::new();
+    for tile in 0..spec.tile_count() {
+        for orientation in 0..4 {
+            let plane = Plane::new_with_shape(Coordinate::new_from_usize(tile, spec), Orientation::from(orientation), shape.clone(), 0);
+            if !plane.is_outside_of_map(spec) {
+                placements.push(plane);
+            }
+        }
+    }
+    placements
+}
+
+/// Every single plane which could still be alive according to a scrapbook:
+/// its head has not been bombarded yet, and none of its tiles is a miss or
+/// belongs to a plane which is known to be killed.
+pub fn candidate_placements(scrapbook: &Board) -> Vec<Plane> {
+    let placements = scrapbook.fleet().distinct_shapes().into_iter()
+        .flat_map(|shape| all_placements(scrapbook.spec(), shape));
+    placements.filter(|plane| {
+        if !scrapbook.empty_indices().contains(&plane.head().as_usize(scrapbook.spec())) {
+            return false;
+        }
+        for tile in plane.coordinate_iterator().filter_map(|t| t) {
+            if scrapbook.misses().contains(&tile) || scrapbook.kills().contains(&tile) {
+                return false;
+            }
+        }
+        !scrapbook.killed_planes().iter().any(|killed| plane.is_overlapping_with(killed))
+    }).collect()
+}
+
+/// The hits which do not belong to a plane known to be killed yet.
+///
+/// When killed planes are revealed, these are the hits outside of them.
+/// Otherwise, every kill is assumed to account for all the hits before it.
+pub fn open_hits(scrapbook: &Board) -> Vec<Coordinate> {
+    let hits = scrapbook.hits();
+    if !scrapbook.killed_planes().is_empty() {
+        return hits.iter()
+            .filter(|hit| !scrapbook.killed_planes().iter().any(|killed| killed.has_tile(hit) || killed.head() == *hit))
+            .cloned()
+            .collect();
+    }
+    let last_kill = hits.iter().rposition(|hit| scrapbook.kills().contains(hit));
+    match last_kill {
+        Some(position) => hits[position+1..].to_vec(),
+        None => hits.clone(),
+    }
+}
+
+/// The untried tile with the best odds according to the `Heatmap`: of being
+/// a head while following up on hits, of being part of a plane otherwise.
+pub fn most_probable_tile<R: Rng>(scrapbook: &Board, rng: &mut R) -> Option<Coordinate> {
+    let heatmap = Heatmap::new(scrapbook);
+    if heatmap.layouts() == 0 {
+        return None;
+    }
+    let targeting = !open_hits(scrapbook).is_empty();
+    let scores: Vec<(usize, f64)> = scrapbook.empty_indices().iter().map(|tile| {
+        let coord = Coordinate::new_from_usize(*tile, scrapbook.spec());
+        let score = match targeting {
+            true => heatmap.head_probability(&coord),
+            false => heatmap.plane_probability(&coord),
+        };
+        (*tile, score)
+    }).collect();
+    pick_best_scored(&scores, scrapbook.spec(), rng)
+}
+
+/// While hunting, the untried tile scoring best by the share of the past
+/// layouts of the opponent in which it held a plane, plus its odds of
+/// holding one by the `Heatmap`; both go from 0 to 1, so the history and
+/// what the scrapbook tells weigh alike. Tiles the heatmap rules out are
+/// skipped, unless it is too costly to work out. `None` when following up on
+/// hits, or when the history was recorded on another map or holds no game.
+fn remembered_tile<R: Rng>(scrapbook: &Board, history: &LayoutHistory, rng: &mut R) -> Option<Coordinate> {
+    if history.games() == 0 || history.spec() != scrapbook.spec() || !open_hits(scrapbook).is_empty() {
+        return None;
+    }
+    let heatmap = Heatmap::new(scrapbook);
+    let scores: Vec<(usize, f64)> = scrapbook.empty_indices().iter().filter_map(|tile| {
+        let coord = Coordinate::new_from_usize(*tile, scrapbook.spec());
+        let odds = heatmap.plane_probability(&coord);
+        if heatmap.layouts() > 0 && odds == 0.0 {
+            return None;
+        }
+        Some((*tile, history.frequency(&coord) + odds))
+    }).collect();
+    pick_best_scored(&scores, scrapbook.spec(), rng)
+}
+
+/// Aims at the head shared by most of the candidate planes which explain the
+/// open hits, or `None` when there is nothing to follow up on.
+pub fn most_likely_head<R: Rng>(scrapbook: &Board, rng: &mut R) -> Option<Coordinate> {
+    let open_hits = open_hits(scrapbook);
+    if open_hits.is_empty() {
+        return None;
+    }
+    let mut best_coverage = 0;
+    let mut head_counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for plane in candidate_placements(scrapbook) {
+        let coverage = open_hits.iter().filter(|hit| plane.has_tile(hit)).count();
+        if coverage == 0 || coverage < best_coverage {
+            continue;
+        }
+        if coverage > best_coverage {
+            best_coverage = coverage;
+            head_counts.clear();
+        }
+        *head_counts.entry(plane.head().as_usize(scrapbook.spec())).or_insert(0) += 1;
+    }
+    pick_most_frequent(&head_counts, scrapbook.spec(), rng)
+}
+
+/// One of the tiles with the highest score, ties broken randomly.
+fn pick_best_scored<R: Rng>(scores: &[(usize, f64)], spec: &BoardSpec, rng: &mut R) -> Option<Coordinate> {
+    let best_score = scores.iter().fold(0.0, |best, &(_, score)| if score > best { score } else { best });
+    let best_tiles: Vec<usize> = scores.iter()
+        .filter(|&&(_, score)| best_score - score < 1e-9)
+        .map(|&(tile, _)| tile)
+        .collect();
+    if best_tiles.is_empty() {
+        return None;
+    }
+    let chosen = rng.gen::<usize>() % best_tiles.len();
+    Some(Coordinate::new_from_usize(best_tiles[chosen], spec))
+}
+
+/// One of the tiles with the highest count, ties broken randomly.
+fn pick_most_frequent<R: Rng>(counts: &BTreeMap<usize, usize>, spec: &BoardSpec, rng: &mut R) -> Option<Coordinate> {
+    let max_count = match counts.values().max() {
+        Some(max_count) => *max_count,
+        None => return None,
+    };
+    let best_tiles: Vec<usize> = counts.iter()
+        .filter(|&(_, count)| *count == max_count)
+        .map(|(tile, _)| *tile)
+        .collect();
+    let chosen = rng.gen::<usize>() % best_tiles.len();
+    Some(Coordinate::new_from_usize(best_tiles[chosen], spec))
+}
@@ -1,98 +1,46 @@
 use std::fmt;
 
+use board_spec::MAX_SIZE;
+
+/// A column of the map, `A` being the leftmost one. Whether it is on the
+/// board at hand depends on the `BoardSpec`.
 #[derive(Debug)]
-pub enum CoordLetter {
-    A, B, C, D, E, F, G, H, I, J,
-}
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub struct CoordLetter(usize);
 
 impl CoordLetter {
     pub fn new(from: &str) -> Option<CoordLetter> {
-        use CoordLetter::*;
-        match from {
-            "A" => Some(A),
-            "B" => Some(B),
-            "C" => Some(C),
-            "D" => Some(D),
-            "E" => Some(E),
-            "F" => Some(F),
-            "G" => Some(G),
-            "H" => Some(H),
-            "I" => Some(I),
-            "J" => Some(J),
-            _ => None
+        let mut chars = from.chars();
+        match (chars.next(), chars.next()) {
+            (Some(letter), None) if letter >= 'A' && letter <= 'Z' => Some(CoordLetter(letter as usize - 'A' as usize)),
+            _ => None,
         }
     }
     pub fn new_moved_by(&self, offset: i32) -> Option<CoordLetter> {
-        let moved_usize: i32 = ((*self as usize) as i32) + offset;
+        let moved_usize: i32 = (self.0 as i32) + offset;
         if moved_usize < 0 {
             return None;
         }
-        if moved_usize > 9 {
+        if moved_usize >= MAX_SIZE as i32 {
             return None;
         }
         return Some(CoordLetter::from(moved_usize as usize));
     }
-}
-
-impl Copy for CoordLetter { }
-impl Clone for CoordLetter {
-    fn clone(&self) -> CoordLetter {
-        *self
-    }
-}
-
-impl PartialEq for CoordLetter {
-    fn eq(&self, other: &CoordLetter) -> bool {
-        use CoordLetter::*;
-        match (self, other) {
-            (&A, &A) => true,
-            (&B, &B) => true,
-            (&C, &C) => true,
-            (&D, &D) => true,
-            (&E, &E) => true,
-            (&F, &F) => true,
-            (&G, &G) => true,
-            (&H, &H) => true,
-            (&I, &I) => true,
-            (&J, &J) => true,
-            _ => false,
-        }
+    pub fn as_usize(&self) -> usize {
+        self.0
     }
 }
 
 impl From<usize> for CoordLetter {
     fn from(u: usize) -> CoordLetter {
-        use CoordLetter::*;
-        match u % 10 {
-            0 => A,
-            1 => B,
-            2 => C,
-            3 => D,
-            4 => E,
-            5 => F,
-            6 => G,
-            7 => H,
-            8 => I,
-            _ => J,
-        }
+        CoordLetter(u % MAX_SIZE)
     }
 }
 
 impl fmt::Display for CoordLetter {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use CoordLetter::*;
-        write!(f, "{}", match self {
-            &A => "A",
-            &B => "B",
-            &C => "C",
-            &D => "D",
-            &E => "E",
-            &F => "F",
-            &G => "G",
-            &H => "H",
-            &I => "I",
-            &J => "J",
-        })
+        write!(f, "{}", (b'A' + self.0 as u8) as char)
     }
 }
-
@@ -6,6 +6,7 @@ use rand::Rng;
 
 use coord_letter::CoordLetter;
 use coord_num::CoordNum;
+use board_spec::BoardSpec;
 
 #[derive(Debug)]
 #[derive(Copy)]
@@ -13,6 +14,9 @@ pub struct Coordinate (CoordLetter, CoordNum);
 
 impl Coordinate {
     pub fn new(from: &str) -> Option<Coordinate> {
+        if from.len() < 2 || !from.is_char_boundary(1) {
+            return None;
+        }
         match (CoordLetter::new(&from[0..1]), CoordNum::new(&from[1..])) {
             (Some(letter), Some(number)) => Some(Coordinate(letter, number)),
             _ => None,
@@ -24,23 +28,30 @@ impl Coordinate {
             _ => None,
         }
     }
-    pub fn new_random_coordinate() -> Coordinate {
-        let mut rng = rand::thread_rng();
-        let rand_x: usize= rng.gen_range(0, 10);
-        let rand_y: usize = rng.gen_range(0, 10);
-        Coordinate ( CoordLetter::from(rand_x), CoordNum::from(rand_y) )
-    }
-    pub fn new_from_usize(num: usize) -> Coordinate {
-        let x : usize = num % 10;
-        let y : usize = num / 10;
+    pub fn new_from_xy(x: usize, y: usize) -> Coordinate {
         Coordinate( CoordLetter::from(x), CoordNum::from(y) )
     }
+    pub fn new_random_coordinate(spec: &BoardSpec) -> Coordinate {
+        Coordinate::new_random_coordinate_with_rng(spec, &mut rand::thread_rng())
+    }
+    pub fn new_random_coordinate_with_rng<R: Rng>(spec: &BoardSpec, rng: &mut R) -> Coordinate {
+        let rand_x: usize= rng.gen_range(0, spec.width);
+        let rand_y: usize = rng.gen_range(0, spec.height);
+        Coordinate::new_from_xy(rand_x, rand_y)
+    }
+    /// The coordinate of a tile index, see `BoardSpec`.
+    pub fn new_from_usize(num: usize, spec: &BoardSpec) -> Coordinate {
+        let x : usize = num % spec.width;
+        let y : usize = num / spec.width;
+        Coordinate::new_from_xy(x, y)
+    }
 
     pub fn as_tuple(&self) -> (usize, usize) {
-        (self.0 as usize, self.1 as usize)
+        (self.0.as_usize(), self.1.as_usize())
     }
-    pub fn as_usize(&self) -> usize {
-        (self.1 as usize) * 10 + (self.0 as usize)
+    /// The tile index of the coordinate, see `BoardSpec`.
+    pub fn as_usize(&self, spec: &BoardSpec) -> usize {
+        self.1.as_usize() * spec.width + self.0.as_usize()
     }
 }
 
@@ -72,12 +83,20 @@ mod unittests {
     use super::*;
     #[test]
     fn read_coordinate() {
-        assert_eq!(Coordinate(CoordLetter::A, CoordNum::Five), Coordinate::new("A5").unwrap());
-        assert_eq!(Coordinate(CoordLetter::B, CoordNum::Seven), Coordinate::new("B7").unwrap());
+        assert_eq!(Coordinate::new_from_xy(0, 4), Coordinate::new("A5").unwrap());
+        assert_eq!(Coordinate::new_from_xy(1, 6), Coordinate::new("B7").unwrap());
+        assert_eq!(Coordinate::new_from_xy(14, 14), Coordinate::new("O15").unwrap());
     }
     #[test]
     fn move_coordinate_within_reach_of_plane() {
-        assert_eq!(Coordinate(CoordLetter::F, CoordNum::Six), Coordinate::new("E5").unwrap().new_moved_by(1, 1).unwrap());
-        assert_eq!(Coordinate(CoordLetter::D, CoordNum::Four), Coordinate::new("E5").unwrap().new_moved_by(-1, -1).unwrap());
+        assert_eq!(Coordinate::new_from_xy(5, 5), Coordinate::new("E5").unwrap().new_moved_by(1, 1).unwrap());
+        assert_eq!(Coordinate::new_from_xy(3, 3), Coordinate::new("E5").unwrap().new_moved_by(-1, -1).unwrap());
+    }
+    #[test]
+    fn tile_index_follows_the_width() {
+        let coord = Coordinate::new("C2").unwrap();
+        assert_eq!(12, coord.as_usize(&BoardSpec::default()));
+        assert_eq!(17, coord.as_usize(&BoardSpec::new(15, 15).unwrap()));
+        assert_eq!(coord, Coordinate::new_from_usize(17, &BoardSpec::new(15, 15).unwrap()));
     }
 }
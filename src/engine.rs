@@ -0,0 +1,118 @@
+use game::Game;
+use game_play::GamePlay;
+use player::Player;
+use coordinate::Coordinate;
+use bombardment_result::BombardmentResult;
+use placement_error::PlacementError;
+
+/// Something that happened while an `Engine` was driving a `Game`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum Event {
+    PlanePlaced { player: usize, plane_id: usize },
+    PlacementRejected { player: usize, error: PlacementError },
+    Bombarded { player: usize, target: Coordinate, result: BombardmentResult },
+    StateChanged(GamePlay),
+    /// The controller of this player has no move ready yet.
+    Waiting(usize),
+    GameOver { winner: usize },
+}
+
+/// Drives a `Game` by asking the controller of the player whose turn it is
+/// for its next move, applying it, and advancing the state machine.
+///
+/// Frontends only have to render the game and react to the emitted events.
+pub struct Engine<'a> {
+    game: &'a mut Game,
+    players: Vec<Box<Player + 'a>>,
+}
+
+impl<'a> Engine<'a> {
+    pub fn new(game: &'a mut Game, players: Vec<Box<Player + 'a>>) -> Engine<'a> {
+        assert_eq!(game.players.len(), players.len(), "one controller per player slot");
+        Engine {
+            game: game,
+            players: players,
+        }
+    }
+    pub fn game(&self) -> &Game {
+        self.game
+    }
+
+    /// Asks the current player for a single move and applies it.
+    pub fn step(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        match self.game.gameplay {
+            GamePlay::PlaceNewPlane(player) => {
+                let (head, orientation) = match self.players[player].next_plane(self.game, player) {
+                    Some(new_plane) => new_plane,
+                    None => {
+                        events.push(Event::Waiting(player));
+                        return events;
+                    },
+                };
+                match self.game.add_new_plane(player, head, orientation) {
+                    Ok(plane_id) => {
+                        events.push(Event::PlanePlaced { player: player, plane_id: plane_id });
+                        self.advance(&mut events);
+                    },
+                    Err(error) => {
+                        debug!("player {} cannot place plane: {}", player, error);
+                        events.push(Event::PlacementRejected { player: player, error: error });
+                    },
+                }
+            },
+            GamePlay::Bombard(player) => {
+                let target = match self.players[player].next_target(self.game, player) {
+                    Some(target) => target,
+                    None => {
+                        events.push(Event::Waiting(player));
+                        return events;
+                    },
+                };
+                let result = self.game.bombard(player, target);
+                let retry = result == BombardmentResult::Retry;
+                events.push(Event::Bombarded { player: player, target: target, result: result });
+                if !retry {
+                    self.advance(&mut events);
+                }
+            },
+            GamePlay::Won(winner) => {
+                events.push(Event::GameOver { winner: winner });
+            },
+        }
+        events
+    }
+
+    /// Keeps stepping until the game is over, or until a step makes no
+    /// progress, e.g. because a controller is waiting for input or its move
+    /// was rejected.
+    pub fn run<F>(&mut self, mut on_event: F) -> GamePlay
+        where F: FnMut(&Game, &Event)
+    {
+        loop {
+            let events = self.step();
+            let mut progressed = false;
+            for event in &events {
+                on_event(self.game, event);
+                match event {
+                    &Event::StateChanged(_) => progressed = true,
+                    _ => { },
+                }
+            }
+            if !progressed || self.game.gameplay.is_over() {
+                break;
+            }
+        }
+        self.game.gameplay.clone()
+    }
+
+    fn advance(&mut self, events: &mut Vec<Event>) {
+        self.game.next_logical_state();
+        events.push(Event::StateChanged(self.game.gameplay.clone()));
+        if let GamePlay::Won(winner) = self.game.gameplay {
+            events.push(Event::GameOver { winner: winner });
+        }
+    }
+}
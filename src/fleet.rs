@@ -0,0 +1,124 @@
+use std::fmt;
+
+use board_spec::BoardSpec;
+use plane_shape::PlaneShape;
+use rules::DEFAULT_FLEET_SIZE;
+
+/// The planes a player places, in the order they are placed, e.g. a bomber
+/// first and then two fighters.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Fleet(Vec<PlaneShape>);
+
+impl Fleet {
+    pub fn new(shapes: Vec<PlaneShape>) -> Fleet {
+        Fleet(shapes)
+    }
+    /// `size` planes of the same `shape`.
+    pub fn new_uniform(size: usize, shape: PlaneShape) -> Fleet {
+        Fleet(vec![shape; size])
+    }
+    /// Reads a fleet of built-in shapes like `1 bomber, 2 fighters`, placed
+    /// in that order. The count may be left out for a single plane.
+    pub fn new_from_str(from: &str) -> Option<Fleet> {
+        let mut shapes = Vec::new();
+        for group in from.split(',') {
+            let words: Vec<&str> = group.split_whitespace().collect();
+            let (count, name) = match words.as_slice() {
+                &[name] => (1, name),
+                &[count, name] => match count.parse::<usize>() {
+                    Ok(count) if count >= 1 => (count, name),
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            let singular = match name.ends_with('s') {
+                true => &name[..name.len() - 1],
+                false => name,
+            };
+            let shape = PlaneShape::new_named(name).or_else(|| PlaneShape::new_named(singular));
+            match shape {
+                Some(shape) => shapes.extend(vec![shape; count]),
+                None => return None,
+            }
+        }
+        Some(Fleet(shapes))
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// The shape of the plane placed in position `index`, from 0.
+    pub fn get(&self, index: usize) -> Option<&PlaneShape> {
+        self.0.get(index)
+    }
+    pub fn shapes(&self) -> &Vec<PlaneShape> {
+        &self.0
+    }
+    /// The shapes in the fleet, each one once, in order of appearance.
+    pub fn distinct_shapes(&self) -> Vec<&PlaneShape> {
+        let mut distinct: Vec<&PlaneShape> = Vec::new();
+        for shape in &self.0 {
+            if !distinct.contains(&shape) {
+                distinct.push(shape);
+            }
+        }
+        distinct
+    }
+    /// How many tiles all the planes cover together.
+    pub fn tile_count(&self) -> usize {
+        self.0.iter().map(|shape| shape.tile_count()).sum()
+    }
+    /// Whether the planes fit on the map in rows, each one in the rectangle
+    /// around it, see `PlaneShape::extent`, all flying north or all flying
+    /// east. Then they can be laid out without searching for a layout.
+    pub fn fits_side_by_side(&self, spec: &BoardSpec) -> bool {
+        let fits_in_rows = |extents: Vec<(usize, usize)>, width: usize, height: usize| {
+            let (mut x, mut y, mut row_height) = (0, 0, 0);
+            for (plane_width, plane_height) in extents {
+                if x + plane_width > width {
+                    x = 0;
+                    y += row_height;
+                    row_height = 0;
+                }
+                if plane_width > width {
+                    return false;
+                }
+                x += plane_width;
+                row_height = row_height.max(plane_height);
+            }
+            y + row_height <= height
+        };
+        let extents: Vec<(usize, usize)> = self.0.iter().map(|shape| shape.extent()).collect();
+        let turned: Vec<(usize, usize)> = extents.iter().map(|&(width, height)| (height, width)).collect();
+        fits_in_rows(extents, spec.width, spec.height) || fits_in_rows(turned, spec.width, spec.height)
+    }
+}
+
+impl Default for Fleet {
+    fn default() -> Fleet {
+        Fleet::new_uniform(DEFAULT_FLEET_SIZE, PlaneShape::default())
+    }
+}
+
+/// Counts consecutive planes of the same shape, like `1 bomber, 2 fighters`.
+impl fmt::Display for Fleet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut groups: Vec<(usize, &str)> = Vec::new();
+        for shape in &self.0 {
+            let same_as_last = groups.last().map(|&(_, name)| name == shape.name()).unwrap_or(false);
+            match same_as_last {
+                true => groups.last_mut().unwrap().0 += 1,
+                false => groups.push((1, shape.name())),
+            }
+        }
+        let groups: Vec<String> = groups.iter()
+            .map(|&(count, name)| format!("{} {}{}", count, name, if count > 1 { "s" } else { "" }))
+            .collect();
+        write!(f, "{}", groups.join(", "))
+    }
+}
@@ -3,139 +3,240 @@ use rand::Rng;
 
 use game_play::GamePlay;
 use board::Board;
+use board_spec::BoardSpec;
+use rules::Rules;
+use fleet::Fleet;
 use coordinate::Coordinate;
+use orientation::Orientation;
 use bombardment_result::BombardmentResult;
+use placement_error::PlacementError;
+use move_log::Move;
 
+/// Everything a single participant owns in a `Game`: the board carrying its
+/// own planes, and the scrapbook where it records the shots it fired.
 #[derive(Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct PlayerSlot {
+    pub board: Board,
+    pub scrapbook: Board,
+}
+
+impl PlayerSlot {
+    pub fn new() -> PlayerSlot {
+        PlayerSlot::new_with_spec(BoardSpec::default())
+    }
+    pub fn new_with_spec(spec: BoardSpec) -> PlayerSlot {
+        PlayerSlot {
+            board: Board::new_with_spec(spec),
+            scrapbook: Board::new_with_spec(spec),
+        }
+    }
+    /// The slot of `player` in a game played by `rules`.
+    pub fn new_with_rules(rules: &Rules, player: usize) -> PlayerSlot {
+        PlayerSlot {
+            board: Board::new_with_mixed_fleet(rules.spec, rules.fleets[player].clone()),
+            scrapbook: Board::new_with_mixed_fleet(rules.spec, rules.opponent_fleet(player).clone()),
+        }
+    }
+    pub fn is_defeated(&self) -> bool {
+        self.board.is_initialized() && self.board.planes().len() == 0
+    }
+}
+
+/// Read back from JSON through `validate`, see `serialize.rs`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
 pub struct Game {
     pub gameplay: GamePlay,
-    pub board_you: Board,
-    pub board_opponent: Board,
-    pub scrapbook_you: Board,
-    pub scrapbook_opponent: Board,
+    pub players: Vec<PlayerSlot>,
     pub reveal_killed: bool,
+    /// Everything that happened so far, see `Replay`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub moves: Vec<Move>,
 }
 
 impl Game {
-    pub fn new_random_starter(reveal_killed: bool) -> Game {
+    pub fn new_random_starter(player_count: usize, reveal_killed: bool) -> Game {
+        Game::new_random_starter_with_rng(player_count, reveal_killed, &mut rand::thread_rng())
+    }
+    pub fn new_random_starter_with_rng<R: Rng>(player_count: usize, reveal_killed: bool, rng: &mut R) -> Game {
+        Game::new_random_starter_with_spec(player_count, reveal_killed, BoardSpec::default(), rng)
+    }
+    pub fn new_random_starter_with_spec<R: Rng>(player_count: usize, reveal_killed: bool, spec: BoardSpec, rng: &mut R) -> Game {
+        Game::new_random_starter_with_rules(reveal_killed, Rules { spec: spec, fleets: vec![Fleet::default(); player_count] }, rng)
+    }
+    pub fn new_random_starter_with_rules<R: Rng>(reveal_killed: bool, rules: Rules, rng: &mut R) -> Game {
+        let starter = GamePlay::new_random_state_with_rng(rules.player_count(), rng).player();
+        Game::new_with_rules(starter, reveal_killed, rules)
+    }
+    pub fn new_with_starter(player_count: usize, starter: usize, reveal_killed: bool) -> Game {
+        Game::new_with_spec(player_count, starter, reveal_killed, BoardSpec::default())
+    }
+    /// A game whose players all play on maps of the size `spec`.
+    pub fn new_with_spec(player_count: usize, starter: usize, reveal_killed: bool, spec: BoardSpec) -> Game {
+        Game::new_with_rules(starter, reveal_killed, Rules { spec: spec, fleets: vec![Fleet::default(); player_count] })
+    }
+    /// A game with one player per fleet of the `rules`.
+    pub fn new_with_rules(starter: usize, reveal_killed: bool, rules: Rules) -> Game {
+        let players = (0..rules.player_count())
+            .map(|player| PlayerSlot::new_with_rules(&rules, player))
+            .collect();
         Game {
-            gameplay: GamePlay::new_random_state(),
-            board_you: Board::new(),
-            board_opponent: Board::new(),
-            scrapbook_you: Board::new(),
-            scrapbook_opponent: Board::new(),
+            gameplay: GamePlay::PlaceNewPlane(starter),
+            players: players,
             reveal_killed: reveal_killed,
+            moves: Vec::new(),
         }
     }
-
-    pub fn next_logical_state(&mut self) {
-        use GamePlay::*;
-        println!("gameplay is {}", self.gameplay);
-        if self.gameplay == YouPlaceNewPlane {
-            if !self.board_opponent.is_initialized() {
-                self.gameplay = OpponentPlacesNewPlane;
-            } else {
-                self.gameplay = OpponentBombards;
+    pub fn spec(&self) -> &BoardSpec {
+        self.players[0].board.spec()
+    }
+    /// Checks a game read back from a file or a client: it needs players,
+    /// all on maps of the same size with boards which pass
+    /// `Board::validate`, and only turns and moves of those players.
+    pub fn validate(&self) -> Result<(), String> {
+        let spec = match self.players.first() {
+            Some(slot) => *slot.board.spec(),
+            None => return Err("a game needs players".to_string()),
+        };
+        for slot in &self.players {
+            for board in &[&slot.board, &slot.scrapbook] {
+                if board.spec() != &spec {
+                    return Err(format!("the maps are not all {}", spec));
+                }
+                board.validate()?;
             }
-            println!("gameplay became {} in {} on {}", self.gameplay, file!(), line!());
-            return;
         }
-        if self.gameplay == OpponentPlacesNewPlane {
-            if !self.board_you.is_initialized() {
-                self.gameplay = YouPlaceNewPlane;
-            } else {
-                self.gameplay = YouBombard;
-            }
-            println!("gameplay became {} in {} on {}", self.gameplay, file!(), line!());
-            return;
+        let is_player = |player: usize| player < self.players.len();
+        let moves_are_valid = self.moves.iter().all(|logged| match logged {
+            &Move::Placed { player, .. } => is_player(player),
+            &Move::Bombarded { player, .. } => is_player(player),
+            &Move::StateChanged(ref gameplay) => is_player(gameplay.player()),
+        });
+        match is_player(self.gameplay.player()) && moves_are_valid {
+            true => Ok(()),
+            false => Err(format!("the moves are not all of the {} players", self.players.len())),
         }
-        if self.gameplay == YouBombard {
-            if self.board_opponent.planes().len() == 0 {
-                self.gameplay = YouWon;
-            } else {
-                self.gameplay = OpponentBombards;
-            }
-            println!("gameplay became {} in {} on {}", self.gameplay, file!(), line!());
-            return;
+    }
+    /// The rules the game was started with.
+    pub fn rules(&self) -> Rules {
+        Rules {
+            spec: *self.spec(),
+            fleets: self.players.iter().map(|slot| slot.board.fleet().clone()).collect(),
         }
-        if self.gameplay == OpponentBombards {
-            if self.board_you.planes().len() == 0 {
-                self.gameplay = OpponentWon;
-            } else {
-                self.gameplay = YouBombard;
+    }
+    pub fn current_player(&self) -> usize {
+        self.gameplay.player()
+    }
+    /// The player that `player` is shooting at: the next one in turn order
+    /// which still has planes in the air.
+    pub fn opponent_of(&self, player: usize) -> Option<usize> {
+        let count = self.players.len();
+        for step in 1..count {
+            let candidate = (player + step) % count;
+            if !self.players[candidate].is_defeated() {
+                return Some(candidate);
             }
-            println!("gameplay became {} in {} on {}", self.gameplay, file!(), line!());
-            return;
         }
-        println!("gameplay stayed {} in {} on {}", self.gameplay, file!(), line!());
+        None
     }
-    pub fn you_hit_at(&mut self, target: &str) -> BombardmentResult {
-        use BombardmentResult::*;
-        match Coordinate::new(target) {
-            None => {
-                Retry
+
+    pub fn next_logical_state(&mut self) {
+        debug!("gameplay is {}", self.gameplay);
+        let before = self.gameplay.clone();
+        self.advance_gameplay();
+        if self.gameplay != before {
+            self.moves.push(Move::StateChanged(self.gameplay.clone()));
+        }
+    }
+    fn advance_gameplay(&mut self) {
+        use GamePlay::*;
+        let count = self.players.len();
+        match self.gameplay {
+            PlaceNewPlane(player) => {
+                for step in 1..count+1 {
+                    let candidate = (player + step) % count;
+                    if !self.players[candidate].board.is_initialized() {
+                        self.gameplay = PlaceNewPlane(candidate);
+                        debug!("gameplay became {} in {} on {}", self.gameplay, file!(), line!());
+                        return;
+                    }
+                }
+                self.gameplay = Bombard((player + 1) % count);
+                debug!("gameplay became {} in {} on {}", self.gameplay, file!(), line!());
             },
-            Some(coord) => {
-                let result = self.board_opponent.hit_at(coord);
-                match result {
-                    Hit => {
-                        self.scrapbook_you.hits_mut().push(coord.clone())
-                    },
-                    Miss => {
-                        self.scrapbook_you.misses_mut().push(coord.clone())
-                    },
-                    Kill => {
-                        let maybe_plane = self.board_opponent.find_plane_at(&coord);
-                        if let Some(plane) = maybe_plane {
-                            println!("revealing plane {} on your scrapbook", plane.id());
-                            self.scrapbook_you.killed_planes_mut().push(plane.clone());
-                        } else {
-                            println!("not revealing plane killed at {}", coord);
-                        }
-                        self.scrapbook_you.kills_mut().push(coord.clone());
-                        self.scrapbook_you.hits_mut().push(coord.clone())
-                    },
-                    Retry => {
-                    },
-                };
-                result
+            Bombard(player) => {
+                match self.opponent_of(player) {
+                    Some(next) => self.gameplay = Bombard(next),
+                    None => self.gameplay = Won(player),
+                }
+                debug!("gameplay became {} in {} on {}", self.gameplay, file!(), line!());
             },
+            Won(_) => {
+                debug!("gameplay stayed {} in {} on {}", self.gameplay, file!(), line!());
+            },
+        }
+    }
+    /// Reports the same errors as `Board::add_new_plane_at`, a head off the
+    /// map of `player` being an invalid coordinate.
+    pub fn add_new_plane_at(&mut self, player: usize, head: &str, orientation: &str) -> Result<usize, PlacementError> {
+        let spec = *self.players[player].board.spec();
+        match (Coordinate::new(head).filter(|coord| spec.contains(coord)), Orientation::new(orientation)) {
+            (Some(head), Some(orientation)) => self.add_new_plane(player, head, orientation),
+            _ => self.players[player].board.add_new_plane_at(head, orientation),
         }
     }
-    pub fn opponent_hits_randomly(&mut self) -> (BombardmentResult, Option<Coordinate>) {
+    pub fn add_new_plane(&mut self, player: usize, head: Coordinate, orientation: Orientation) -> Result<usize, PlacementError> {
+        let plane_id = self.players[player].board.add_new_plane(head, orientation.clone())?;
+        self.moves.push(Move::Placed { player: player, head: head, orientation: orientation });
+        Ok(plane_id)
+    }
+    pub fn hit_at(&mut self, player: usize, target: &str) -> BombardmentResult {
+        match Coordinate::new(target) {
+            None => BombardmentResult::Retry,
+            Some(coord) => self.bombard(player, coord),
+        }
+    }
+    /// `player` bombards `coord` on the board of its current opponent, and
+    /// the outcome is recorded on the player's scrapbook.
+    pub fn bombard(&mut self, player: usize, coord: Coordinate) -> BombardmentResult {
         use BombardmentResult::*;
-        let ref mut scrapbook_opponent = &mut self.scrapbook_opponent;
-        if 0 == scrapbook_opponent.empty_indices().len() {
-            return (Retry, None)
+        let target = match self.opponent_of(player) {
+            Some(target) => target,
+            None => return Retry,
+        };
+        let spec = *self.spec();
+        if !spec.contains(&coord) || !self.players[player].scrapbook.empty_indices_mut().remove(&coord.as_usize(&spec)) {
+            return Retry;
         }
-        let wanted : usize = rand::thread_rng().gen::<usize>() % scrapbook_opponent.empty_indices().len();
-
-        let (tile_num, flag) = {
-            let ref mut indices = scrapbook_opponent.empty_indices_mut();
-            let tile_num = *indices.iter().nth(wanted).unwrap();
-            let flag = indices.remove(&tile_num);
-            (tile_num, flag)
+        let result = self.players[target].board.hit_at(coord);
+        let revealed = match result {
+            Kill(_) if self.reveal_killed => self.players[target].board.find_plane_at(&coord).cloned(),
+            _ => None,
         };
-
-        if flag {
-            let tile = Coordinate::new_from_usize(tile_num);
-            let result = self.board_you.hit_at(tile);
-            match result {
-                Hit => {
-                    scrapbook_opponent.hits_mut().push(tile.clone())
-                },
-                Miss => {
-                    scrapbook_opponent.misses_mut().push(tile.clone())
-                },
-                Kill => {
-                    scrapbook_opponent.kills_mut().push(tile.clone())
-                },
-                Retry => {
-                },
-            };
-            return (result, Some(tile))
+        let ref mut scrapbook = self.players[player].scrapbook;
+        match result {
+            Hit => {
+                scrapbook.hits_mut().push(coord.clone())
+            },
+            Miss => {
+                scrapbook.misses_mut().push(coord.clone())
+            },
+            Kill(_) => {
+                if let Some(plane) = revealed {
+                    debug!("revealing plane {} on the scrapbook of player {}", plane.id(), player);
+                    scrapbook.killed_planes_mut().push(plane);
+                }
+                scrapbook.kills_mut().push(coord.clone());
+                scrapbook.hits_mut().push(coord.clone())
+            },
+            Retry => {
+            },
+        };
+        if result != Retry {
+            self.moves.push(Move::Bombarded { player: player, target: coord, result: result.clone() });
         }
-        (Retry, None)
+        result
     }
 }
 
@@ -3,37 +3,36 @@ use std::fmt;
 use rand;
 use rand::Rng;
 
+/// The state machine of a `Game`, keyed by the index of the player slot
+/// whose turn it is.
 #[derive(Debug)]
 #[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum GamePlay {
-    YouPlaceNewPlane,
-    OpponentPlacesNewPlane,
-    YouBombard,
-    OpponentBombards,
-    YouWon,
-    OpponentWon,
+    PlaceNewPlane(usize),
+    Bombard(usize),
+    Won(usize),
 }
 
 impl GamePlay {
-    pub fn new_random_state() -> GamePlay {
+    pub fn new_random_state(player_count: usize) -> GamePlay {
+        GamePlay::new_random_state_with_rng(player_count, &mut rand::thread_rng())
+    }
+    pub fn new_random_state_with_rng<R: Rng>(player_count: usize, rng: &mut R) -> GamePlay {
+        GamePlay::PlaceNewPlane(rng.gen_range(0, player_count))
+    }
+    pub fn player(&self) -> usize {
         use GamePlay::*;
-        let mut rng = rand::thread_rng();
-        match rng.gen() {
-            true => YouPlaceNewPlane,
-            false => OpponentPlacesNewPlane,
+        match self {
+            &PlaceNewPlane(player) => player,
+            &Bombard(player) => player,
+            &Won(player) => player,
         }
     }
-}
-impl PartialEq for GamePlay {
-    fn eq(&self, other: &GamePlay) -> bool {
-        use GamePlay::*;
-        match (self, other) {
-            (&YouPlaceNewPlane, &YouPlaceNewPlane) => true,
-            (&OpponentPlacesNewPlane, &OpponentPlacesNewPlane) => true,
-            (&YouBombard, &YouBombard) => true,
-            (&OpponentBombards, &OpponentBombards) => true,
-            (&YouWon, &YouWon) => true,
-            (&OpponentWon, &OpponentWon) => true,
+    pub fn is_over(&self) -> bool {
+        match self {
+            &GamePlay::Won(_) => true,
             _ => false,
         }
     }
@@ -42,14 +41,10 @@ impl PartialEq for GamePlay {
 impl fmt::Display for GamePlay {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use GamePlay::*;
-        write!(f, "{}", match self {
-            &YouPlaceNewPlane => "YouPlaceNewPlane",
-            &OpponentPlacesNewPlane => "OpponentPlacesNewPlane",
-            &YouBombard => "YouBombard",
-            &OpponentBombards => "OpponentBombards",
-            &YouWon => "YouWon",
-            &OpponentWon => "OpponentWon",
-        })
+        match self {
+            &PlaceNewPlane(player) => write!(f, "PlaceNewPlane({})", player),
+            &Bombard(player) => write!(f, "Bombard({})", player),
+            &Won(player) => write!(f, "Won({})", player),
+        }
     }
 }
-
@@ -0,0 +1,292 @@
+use std::fmt;
+
+use board::Board;
+use board_spec::{BoardSpec, MAX_SIZE};
+use plane::Plane;
+use coordinate::Coordinate;
+use coord_letter::CoordLetter;
+use bombardment_strategy::all_placements;
+
+/// How many partial layouts the search may look at before giving up, which
+/// happens with the default fleet on maps larger than 12x12. It bounds a
+/// search to about a tenth of a second in release builds, since the AI runs
+/// one before every shot.
+const MAX_SEARCH_STEPS: usize = 2_000_000;
+/// Enough 64 bit words for the tiles of the largest map.
+const TILE_MASK_WORDS: usize = (MAX_SIZE * MAX_SIZE + 63) / 64;
+
+/// A set of tiles, one bit per tile index.
+#[derive(Clone)]
+#[derive(Copy)]
+struct TileMask([u64; TILE_MASK_WORDS]);
+
+impl TileMask {
+    fn new() -> TileMask {
+        TileMask([0; TILE_MASK_WORDS])
+    }
+    fn insert(&mut self, tile: usize) {
+        self.0[tile / 64] |= 1 << (tile % 64);
+    }
+    fn union(&self, other: &TileMask) -> TileMask {
+        let mut result = *self;
+        for i in 0..TILE_MASK_WORDS {
+            result.0[i] |= other.0[i];
+        }
+        result
+    }
+    fn intersects(&self, other: &TileMask) -> bool {
+        (0..TILE_MASK_WORDS).any(|i| self.0[i] & other.0[i] != 0)
+    }
+    fn contains_all(&self, other: &TileMask) -> bool {
+        (0..TILE_MASK_WORDS).all(|i| self.0[i] & other.0[i] == other.0[i])
+    }
+}
+
+/// A plane which could be part of a layout, with its tiles precomputed.
+struct Candidate {
+    plane: Plane,
+    head: TileMask,
+    body: TileMask,
+    all: TileMask,
+}
+
+impl Candidate {
+    fn new(plane: Plane, spec: &BoardSpec) -> Candidate {
+        let mut head = TileMask::new();
+        head.insert(plane.head().as_usize(spec));
+        let mut body = TileMask::new();
+        for tile in plane.tile_iterator(spec) {
+            body.insert(tile);
+        }
+        Candidate {
+            plane: plane,
+            head: head,
+            body: body,
+            all: head.union(&body),
+        }
+    }
+}
+
+/// For every tile of a scrapbook, the probability that it holds a plane head
+/// or a plane body, over all the complete layouts of the opponent's fleet
+/// which are consistent with what the scrapbook knows.
+///
+/// Grids are indexed by row first, like `grid[y][x]`.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Heatmap {
+    heads: Vec<Vec<f64>>,
+    bodies: Vec<Vec<f64>>,
+    layouts: usize,
+}
+
+impl Heatmap {
+    pub fn new(scrapbook: &Board) -> Heatmap {
+        let spec = scrapbook.spec();
+        let mut misses = TileMask::new();
+        for miss in scrapbook.misses() {
+            misses.insert(miss.as_usize(spec));
+        }
+        let mut kills = TileMask::new();
+        for kill in scrapbook.kills() {
+            kills.insert(kill.as_usize(spec));
+        }
+        let mut hits = TileMask::new();
+        for hit in scrapbook.hits() {
+            if !scrapbook.kills().contains(hit) {
+                hits.insert(hit.as_usize(spec));
+            }
+        }
+
+        let known: Vec<Candidate> = scrapbook.killed_planes().iter()
+            .map(|plane| Candidate::new(plane.clone(), spec))
+            .collect();
+        let mut fixed = TileMask::new();
+        for candidate in &known {
+            fixed = fixed.union(&candidate.all);
+        }
+        // The candidates of every shape of the fleet follow each other, each
+        // shape with as many planes left to place as are not known yet.
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let mut groups: Vec<Group> = Vec::new();
+        let mut consistent = known.iter().all(|candidate| scrapbook.fleet().shapes().contains(candidate.plane.shape()));
+        for shape in scrapbook.fleet().distinct_shapes() {
+            let in_fleet = scrapbook.fleet().shapes().iter().filter(|other| *other == shape).count();
+            let killed = known.iter().filter(|candidate| candidate.plane.shape() == shape).count();
+            consistent = consistent && killed <= in_fleet;
+            candidates.extend(all_placements(spec, shape).into_iter()
+                .map(|plane| Candidate::new(plane, spec))
+                .filter(|candidate| {
+                    let head = candidate.plane.head().as_usize(spec);
+                    let head_is_possible = scrapbook.empty_indices().contains(&head) || kills.intersects(&candidate.head);
+                    head_is_possible
+                        && !candidate.all.intersects(&misses)
+                        && !candidate.body.intersects(&kills)
+                        && !candidate.all.intersects(&fixed)
+                }));
+            groups.push(Group { end: candidates.len(), count: in_fleet.saturating_sub(killed) });
+        }
+
+        let mut layout_counts = vec![0usize; candidates.len()];
+        let mut layouts = 0;
+        let fleet_size = scrapbook.fleet_size();
+        if consistent {
+            let mut search = Search {
+                candidates: &candidates,
+                groups: &groups,
+                hits: hits,
+                kills: kills,
+                chosen: Vec::with_capacity(fleet_size),
+                layout_counts: &mut layout_counts,
+                layouts: 0,
+                steps_left: MAX_SEARCH_STEPS,
+            };
+            let mut heads = TileMask::new();
+            let mut bodies = TileMask::new();
+            for candidate in &known {
+                heads = heads.union(&candidate.head);
+                bodies = bodies.union(&candidate.body);
+            }
+            search.start_group(0, heads, bodies);
+            if search.steps_left > 0 {
+                layouts = search.layouts;
+            }
+        }
+
+        let mut heads = vec![vec![0.0; spec.width]; spec.height];
+        let mut bodies = vec![vec![0.0; spec.width]; spec.height];
+        if layouts > 0 {
+            let total = layouts as f64;
+            for (candidate, count) in candidates.iter().zip(layout_counts.iter()) {
+                if *count == 0 {
+                    continue;
+                }
+                let share = *count as f64 / total;
+                let (x, y) = candidate.plane.head().as_tuple();
+                heads[y][x] += share;
+                for tile in candidate.plane.coordinate_iterator().filter_map(|t| t) {
+                    let (x, y) = tile.as_tuple();
+                    bodies[y][x] += share;
+                }
+            }
+            for candidate in &known {
+                let (x, y) = candidate.plane.head().as_tuple();
+                heads[y][x] = 1.0;
+                for tile in candidate.plane.coordinate_iterator().filter_map(|t| t) {
+                    let (x, y) = tile.as_tuple();
+                    bodies[y][x] = 1.0;
+                }
+            }
+        }
+        Heatmap {
+            heads: heads,
+            bodies: bodies,
+            layouts: layouts,
+        }
+    }
+    /// How many complete layouts agree with the scrapbook, 0 if there were
+    /// too many to count, in which case all the probabilities are 0 too.
+    pub fn layouts(&self) -> usize {
+        self.layouts
+    }
+    pub fn heads(&self) -> &Vec<Vec<f64>> {
+        &self.heads
+    }
+    pub fn bodies(&self) -> &Vec<Vec<f64>> {
+        &self.bodies
+    }
+    pub fn head_probability(&self, at: &Coordinate) -> f64 {
+        let (x, y) = at.as_tuple();
+        self.heads[y][x]
+    }
+    pub fn body_probability(&self, at: &Coordinate) -> f64 {
+        let (x, y) = at.as_tuple();
+        self.bodies[y][x]
+    }
+    /// The probability that bombarding this tile hits anything at all.
+    pub fn plane_probability(&self, at: &Coordinate) -> f64 {
+        self.head_probability(at) + self.body_probability(at)
+    }
+}
+
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.heads.first().map(|row| row.len()).unwrap_or(0);
+        write!(f, "  ")?;
+        for x in 0..width {
+            write!(f, "  {}", CoordLetter::from(x))?;
+        }
+        write!(f, "\n")?;
+        for y in 0..self.heads.len() {
+            write!(f, "{:>2}", y + 1)?;
+            for x in 0..width {
+                let percent = ((self.heads[y][x] + self.bodies[y][x]) * 100.0).round() as usize;
+                write!(f, " {:>2}", if percent >= 100 { 99 } else { percent })?;
+            }
+            write!(f, "\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// The candidates of one shape, which end before `end` and start where those
+/// of the previous shape end, of which `count` planes are part of a layout.
+struct Group {
+    end: usize,
+    count: usize,
+}
+
+/// Depth-first enumeration of the non-overlapping combinations of candidates
+/// which put a body over every hit and a head under every kill, with the
+/// planes of every shape of the fleet.
+struct Search<'a> {
+    candidates: &'a Vec<Candidate>,
+    groups: &'a Vec<Group>,
+    hits: TileMask,
+    kills: TileMask,
+    chosen: Vec<usize>,
+    layout_counts: &'a mut Vec<usize>,
+    layouts: usize,
+    steps_left: usize,
+}
+
+impl<'a> Search<'a> {
+    /// Moves on to the planes of the shape `group`, or counts the layout once
+    /// the planes of all shapes are chosen.
+    fn start_group(&mut self, group: usize, heads: TileMask, bodies: TileMask) {
+        if group == self.groups.len() {
+            if bodies.contains_all(&self.hits) && heads.contains_all(&self.kills) {
+                self.layouts += 1;
+                for index in &self.chosen {
+                    self.layout_counts[*index] += 1;
+                }
+            }
+            return;
+        }
+        let start = if group == 0 { 0 } else { self.groups[group - 1].end };
+        let count = self.groups[group].count;
+        self.run(group, start, count, heads, bodies);
+    }
+    /// Picks the `remaining` planes of the shape `group` among its candidates
+    /// from `start` on.
+    fn run(&mut self, group: usize, start: usize, remaining: usize, heads: TileMask, bodies: TileMask) {
+        if self.steps_left == 0 {
+            return;
+        }
+        self.steps_left -= 1;
+        if remaining == 0 {
+            self.start_group(group + 1, heads, bodies);
+            return;
+        }
+        let occupied = heads.union(&bodies);
+        for index in start..self.groups[group].end {
+            let candidate = &self.candidates[index];
+            if candidate.all.intersects(&occupied) {
+                continue;
+            }
+            self.chosen.push(index);
+            self.run(group, index + 1, remaining - 1, heads.union(&candidate.head), bodies.union(&candidate.body));
+            self.chosen.pop();
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+use std::error::Error;
+
+use placement_error::PlacementError;
+
+/// Why a layout string like `"E5N B2W H8S"` does not make a complete board.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum LayoutError {
+    /// The plane written `plane`, at `position` in the layout counting from
+    /// 1, cannot be placed. Overlaps list the positions of the other planes.
+    Plane { position: usize, plane: String, error: PlacementError },
+    /// The layout lists this many planes fewer than the fleet has.
+    MissingPlanes(usize),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use LayoutError::*;
+        match self {
+            &Plane { position, ref plane, ref error } => write!(f, "plane {} ({}): {}", position, plane, error),
+            &MissingPlanes(1) => write!(f, "1 plane is missing"),
+            &MissingPlanes(missing) => write!(f, "{} planes are missing", missing),
+        }
+    }
+}
+
+impl Error for LayoutError { }
@@ -0,0 +1,87 @@
+use board::Board;
+use board_spec::BoardSpec;
+use coordinate::Coordinate;
+
+/// Where the planes of a player were over their past games, tile by tile, so
+/// that an AI can look there first. Read back from JSON through
+/// `new_from_counts`, see `serialize.rs`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct LayoutHistory {
+    spec: BoardSpec,
+    games: usize,
+    /// One count per tile index.
+    heads: Vec<usize>,
+    bodies: Vec<usize>,
+}
+
+impl LayoutHistory {
+    pub fn new(spec: BoardSpec) -> LayoutHistory {
+        LayoutHistory {
+            spec: spec,
+            games: 0,
+            heads: vec![0; spec.tile_count()],
+            bodies: vec![0; spec.tile_count()],
+        }
+    }
+    /// A history of `games` layouts with the counts of heads and bodies of
+    /// every tile, or `None` when there is not one count per tile of the map.
+    pub fn new_from_counts(spec: BoardSpec, games: usize, heads: Vec<usize>, bodies: Vec<usize>) -> Option<LayoutHistory> {
+        let is_valid = BoardSpec::new(spec.width, spec.height).is_some()
+            && heads.len() == spec.tile_count()
+            && bodies.len() == spec.tile_count();
+        match is_valid {
+            true => Some(LayoutHistory { spec: spec, games: games, heads: heads, bodies: bodies }),
+            false => None,
+        }
+    }
+    pub fn spec(&self) -> &BoardSpec {
+        &self.spec
+    }
+    /// How many layouts were recorded.
+    pub fn games(&self) -> usize {
+        self.games
+    }
+    /// Records the planes of a board, killed ones included. A board on a map
+    /// of another size starts the history over, since its tiles do not match.
+    pub fn record(&mut self, board: &Board) {
+        if board.spec() != &self.spec {
+            *self = LayoutHistory::new(*board.spec());
+        }
+        self.games += 1;
+        for plane in board.planes().iter().chain(board.killed_planes().iter()) {
+            self.heads[plane.head().as_usize(&self.spec)] += 1;
+            for tile in plane.tile_iterator(&self.spec) {
+                self.bodies[tile] += 1;
+            }
+        }
+    }
+    pub fn head_count(&self, at: &Coordinate) -> usize {
+        match self.spec.contains(at) {
+            true => self.heads[at.as_usize(&self.spec)],
+            false => 0,
+        }
+    }
+    pub fn body_count(&self, at: &Coordinate) -> usize {
+        match self.spec.contains(at) {
+            true => self.bodies[at.as_usize(&self.spec)],
+            false => 0,
+        }
+    }
+    /// The share of the recorded games in which a plane covered the tile,
+    /// from 0 to 1.
+    pub fn frequency(&self, at: &Coordinate) -> f64 {
+        match self.games {
+            0 => 0.0,
+            games => (self.head_count(at) + self.body_count(at)) as f64 / games as f64,
+        }
+    }
+}
+
+impl Default for LayoutHistory {
+    fn default() -> LayoutHistory {
+        LayoutHistory::new(BoardSpec::default())
+    }
+}
@@ -2,15 +2,27 @@
 /// =====
 ///
 /// * API clean-ups
-/// * the concept of a `Player`
-/// * AI improvements, levels, aiming heuristics
-///   * infer level from planes placed on board (beginner, intermediate, advanced)
-///   * set AI level player heuristics (beginner, intermediate, advanced, hack)
+/// * AI improvements, levels
 /// * users and tournaments
 /// * different UIs: web, console, GUI, OpenGl
-/// * better testability, e.g. stubbing out random generators
 /// * internationalization, translation
 extern crate rand;
+#[macro_use]
+extern crate log;
+#[cfg(feature = "serialize")]
+extern crate serde;
+#[cfg(feature = "serialize")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "serialize")]
+#[cfg_attr(test, macro_use)]
+extern crate serde_json;
+
+mod board_spec;
+pub use self::board_spec::{BoardSpec, MIN_SIZE, MAX_SIZE};
+
+mod rules;
+pub use self::rules::{Rules, DEFAULT_FLEET_SIZE};
 
 mod coord_letter;
 pub use self::coord_letter::CoordLetter;
@@ -24,20 +36,82 @@ pub use self::coordinate::Coordinate;
 mod plane;
 pub use self::plane::Plane;
 
+mod plane_shape;
+pub use self::plane_shape::PlaneShape;
+
+mod fleet;
+pub use self::fleet::Fleet;
+
 mod orientation;
 pub use self::orientation::Orientation;
 
+#[cfg(feature = "serialize")]
+mod serialize;
+
 mod board;
 pub use self::board::Board;
 
+mod placement_error;
+pub use self::placement_error::PlacementError;
+
+mod layout_error;
+pub use self::layout_error::LayoutError;
+
 mod bombardment_result;
 pub use self::bombardment_result::BombardmentResult;
 
 mod game;
-pub use self::game::Game;
+pub use self::game::{Game, PlayerSlot};
 
 mod game_play;
 pub use self::game_play::GamePlay;
 
+mod player;
+pub use self::player::Player;
+
+mod bombardment_strategy;
+pub use self::bombardment_strategy::BombardmentStrategy;
+
+mod heatmap;
+pub use self::heatmap::Heatmap;
+
+mod shot_history;
+pub use self::shot_history::ShotHistory;
+
+mod layout_history;
+pub use self::layout_history::LayoutHistory;
+
+mod player_stats;
+pub use self::player_stats::PlayerStats;
+
+mod placement_strategy;
+pub use self::placement_strategy::PlacementStrategy;
+
+mod placement_analysis;
+pub use self::placement_analysis::PlacementAnalysis;
+
+mod ai_level;
+pub use self::ai_level::AiLevel;
+
+mod ai_player;
+pub use self::ai_player::AiPlayer;
+
+mod queued_player;
+pub use self::queued_player::QueuedPlayer;
+
+mod move_log;
+pub use self::move_log::{Move, Replay};
+
+mod engine;
+pub use self::engine::{Engine, Event};
+
+mod simulation;
+pub use self::simulation::{Simulation, Contender, GameOutcome, Statistics, MatchUp, DEFAULT_THREADS};
+
+#[cfg(feature = "serialize")]
+mod saved_game;
+#[cfg(feature = "serialize")]
+pub use self::saved_game::{SavedGame, SaveError, SAVE_FORMAT_VERSION};
+
 #[cfg(test)]
 mod unittests;
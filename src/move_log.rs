@@ -0,0 +1,135 @@
+use std::fmt;
+
+#[cfg(feature = "serialize")]
+use serde_json;
+
+use game::Game;
+use game_play::GamePlay;
+use rules::Rules;
+use coordinate::Coordinate;
+use orientation::Orientation;
+use bombardment_result::BombardmentResult;
+
+/// One entry of the log a `Game` keeps of everything that happened to it, in
+/// order. Rejected placements and retried bombardments change nothing and
+/// are not recorded.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Move {
+    Placed { player: usize, head: Coordinate, orientation: Orientation },
+    Bombarded { player: usize, target: Coordinate, result: BombardmentResult },
+    StateChanged(GamePlay),
+}
+
+impl Move {
+    /// Whether this was decided by a player, as opposed to the state machine.
+    pub fn is_action(&self) -> bool {
+        match self {
+            &Move::StateChanged(_) => false,
+            _ => true,
+        }
+    }
+    /// The move in JSON as anyone may see it: placements only tell which
+    /// player placed a plane, `{"Placed": {"player": 1}}`, so that nobody
+    /// learns where the planes of the others are.
+    #[cfg(feature = "serialize")]
+    pub fn to_public_json(&self) -> serde_json::Value {
+        let public = match self {
+            &Move::Placed { player, .. } => serde_json::to_value(HiddenPlacement::Placed { player: player }),
+            _ => serde_json::to_value(self),
+        };
+        public.expect("game types always serialize")
+    }
+}
+
+/// A `Move::Placed` without where the plane went.
+#[cfg(feature = "serialize")]
+#[derive(Serialize)]
+enum HiddenPlacement {
+    Placed { player: usize },
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Move::*;
+        match self {
+            &Placed { player, ref head, ref orientation } => write!(f, "player {} placed a plane at {} facing {}", player, head, orientation),
+            &Bombarded { player, ref target, ref result } => write!(f, "player {} bombarded {}: {:?}", player, target, result),
+            &StateChanged(ref gameplay) => write!(f, "gameplay became {}", gameplay),
+        }
+    }
+}
+
+/// Rebuilds a `Game` from its move log, at any ply, a ply being one action
+/// of a player together with the state change it caused.
+#[derive(Clone)]
+pub struct Replay {
+    starter: usize,
+    reveal_killed: bool,
+    rules: Rules,
+    moves: Vec<Move>,
+}
+
+impl Replay {
+    pub fn new(game: &Game) -> Replay {
+        let starter = game.moves.iter()
+            .filter_map(|logged| match logged {
+                &Move::Placed { player, .. } => Some(player),
+                _ => None,
+            })
+            .next()
+            .unwrap_or(game.current_player());
+        Replay {
+            starter: starter,
+            reveal_killed: game.reveal_killed,
+            rules: game.rules(),
+            moves: game.moves.clone(),
+        }
+    }
+    pub fn moves(&self) -> &Vec<Move> {
+        &self.moves
+    }
+    /// How many plies there are to step through.
+    pub fn len(&self) -> usize {
+        self.moves.iter().filter(|logged| logged.is_action()).count()
+    }
+    /// The action which brought the game to `ply`, if any.
+    pub fn action_at(&self, ply: usize) -> Option<&Move> {
+        match ply {
+            0 => None,
+            _ => self.moves.iter().filter(|logged| logged.is_action()).nth(ply - 1),
+        }
+    }
+    /// The game after the first `ply` actions; ply 0 is the empty game.
+    pub fn game_at(&self, ply: usize) -> Game {
+        let mut game = Game::new_with_rules(self.starter, self.reveal_killed, self.rules.clone());
+        let mut actions = 0;
+        for logged in &self.moves {
+            match logged {
+                &Move::Placed { player, head, ref orientation } => {
+                    if actions == ply {
+                        break;
+                    }
+                    actions += 1;
+                    if let Err(error) = game.add_new_plane(player, head, orientation.clone()) {
+                        warn!("replayed placement of player {} at {} failed: {}", player, head, error);
+                    }
+                },
+                &Move::Bombarded { player, target, .. } => {
+                    if actions == ply {
+                        break;
+                    }
+                    actions += 1;
+                    game.bombard(player, target);
+                },
+                &Move::StateChanged(ref gameplay) => {
+                    game.gameplay = gameplay.clone();
+                    game.moves.push(logged.clone());
+                },
+            }
+        }
+        game
+    }
+}
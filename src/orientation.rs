@@ -1,6 +1,8 @@
 use std::fmt;
 
+#[derive(Debug)]
 #[derive(Clone)]
+#[derive(PartialEq)]
 pub enum Orientation {
     North,
     East,
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use ai_level::AiLevel;
+use board::Board;
+use coordinate::Coordinate;
+use layout_history::LayoutHistory;
+use placement_strategy::coverage;
+
+/// The share of body tiles along the sides of the map from which a layout
+/// counts as making full use of them.
+const SEASONED_EDGE_OVERLAP: f64 = 0.25;
+/// The skill from which a player counts as intermediate, then as advanced.
+const INTERMEDIATE_SKILL: f64 = 0.4;
+const ADVANCED_SKILL: f64 = 0.6;
+
+/// What the layout of a player gives away about their skill. Beginners tend
+/// to point all their planes the same way and to put them in the middle of
+/// the map, right where hunting starts.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct PlacementAnalysis {
+    /// How evenly the planes are spread over the four orientations, from 0
+    /// when they all fly the same way to 1.
+    pub entropy: f64,
+    /// The share of body tiles on the sides of the map.
+    pub edge_overlap: f64,
+    /// How close the heads are to where planes fit in the most ways, which
+    /// is where a hunting opponent aims first, from 0 for the corners to 1.
+    pub head_exposure: f64,
+    /// How little the common layouts of a history give the layout away, see
+    /// `new_with_history`; `None` without such a history.
+    pub history_entropy: Option<f64>,
+}
+
+impl PlacementAnalysis {
+    pub fn new(board: &Board) -> PlacementAnalysis {
+        let spec = board.spec();
+        let planes = board.planes().iter().chain(board.killed_planes().iter());
+
+        let mut orientations: BTreeMap<String, usize> = BTreeMap::new();
+        let mut plane_count = 0;
+        let mut body_tiles = 0;
+        let mut edge_tiles = 0;
+        let mut exposure = 0.0;
+        let coverage = coverage(spec, board.fleet());
+        let most_covered = coverage.iter().cloned().fold(0.0, f64::max);
+        for plane in planes {
+            plane_count += 1;
+            *orientations.entry(format!("{}", plane.orientation())).or_insert(0) += 1;
+            for tile in plane.coordinate_iterator_on(spec).filter_map(|tile| tile) {
+                let (x, y) = tile.as_tuple();
+                body_tiles += 1;
+                if x == 0 || y == 0 || x == spec.width - 1 || y == spec.height - 1 {
+                    edge_tiles += 1;
+                }
+            }
+            if most_covered > 0.0 {
+                exposure += coverage[plane.head().as_usize(spec)] / most_covered;
+            }
+        }
+        if plane_count == 0 {
+            return PlacementAnalysis { entropy: 0.0, edge_overlap: 0.0, head_exposure: 0.0, history_entropy: None };
+        }
+
+        // Normalized by the most orientations the planes could take, so that
+        // two planes flying different ways are as varied as can be.
+        let most_varied = (plane_count.min(4) as f64).log2();
+        let entropy = match most_varied > 0.0 {
+            true => {
+                let total = plane_count as f64;
+                orientations.values().map(|&count| {
+                    let share = count as f64 / total;
+                    share * (1.0 / share).log2()
+                }).sum::<f64>() / most_varied
+            },
+            false => 1.0,
+        };
+        PlacementAnalysis {
+            entropy: entropy,
+            edge_overlap: edge_tiles as f64 / body_tiles as f64,
+            head_exposure: exposure / plane_count as f64,
+            history_entropy: None,
+        }
+    }
+    /// Same as `new`, also weighing the layout against the common layouts
+    /// of `history`, like the past ones of the player, as the cross entropy
+    /// of its tiles against how often planes covered them there: from 0 when
+    /// the planes are right where they always were to 1 when the history
+    /// tells no more about them than chance. Left out when the history is
+    /// empty or of another map.
+    pub fn new_with_history(board: &Board, history: &LayoutHistory) -> PlacementAnalysis {
+        let mut analysis = PlacementAnalysis::new(board);
+        let spec = board.spec();
+        if history.spec() != spec || history.games() == 0 {
+            return analysis;
+        }
+        let tiles: Vec<Coordinate> = board.planes().iter().chain(board.killed_planes().iter())
+            .flat_map(|plane| Some(*plane.head()).into_iter().chain(plane.coordinate_iterator_on(spec).filter_map(|tile| tile)))
+            .collect();
+        if tiles.is_empty() {
+            return analysis;
+        }
+        // Every tile counts once more than recorded, so that none is ruled
+        // out.
+        let count = |at: &Coordinate| (history.head_count(at) + history.body_count(at) + 1) as f64;
+        let total: f64 = (0..spec.tile_count()).map(|index| count(&Coordinate::new_from_usize(index, spec))).sum();
+        let cross_entropy = tiles.iter().map(|tile| (total / count(tile)).log2()).sum::<f64>() / tiles.len() as f64;
+        // Never below that of a history of this very layout only, the
+        // entropy of the layout itself; above that of a history spread evenly
+        // over the map when the planes are where they hardly ever were.
+        let lowest = (tiles.len() as f64).log2();
+        let even = (spec.tile_count() as f64).log2();
+        analysis.history_entropy = Some(((cross_entropy - lowest) / (even - lowest)).min(1.0));
+        analysis
+    }
+    /// Sums the analysis up, from 0 for the plainest layouts to 1.
+    pub fn skill(&self) -> f64 {
+        let edges = (self.edge_overlap / SEASONED_EDGE_OVERLAP).min(1.0);
+        let plain = self.entropy + edges + (1.0 - self.head_exposure);
+        match self.history_entropy {
+            Some(history_entropy) => (plain + history_entropy) / 4.0,
+            None => plain / 3.0,
+        }
+    }
+    /// The AI level which makes a fair opponent for the player.
+    pub fn level(&self) -> AiLevel {
+        let skill = self.skill();
+        if skill < INTERMEDIATE_SKILL {
+            AiLevel::Beginner
+        } else if skill < ADVANCED_SKILL {
+            AiLevel::Intermediate
+        } else {
+            AiLevel::Advanced
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+use std::error::Error;
+
+/// Why a plane could not be sent to mission.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum PlacementError {
+    InvalidCoordinate(String),
+    InvalidOrientation(String),
+    OutOfMap,
+    /// The ids of all the planes already on the board which would be overlapped.
+    Overlap { with: Vec<usize> },
+    GameAlreadyStarted,
+    TooManyPlanes,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PlacementError::*;
+        match self {
+            &InvalidCoordinate(ref head) => write!(f, "{} is not a coordinate on the map", head),
+            &InvalidOrientation(ref orientation) => write!(f, "{} is not an orientation", orientation),
+            &OutOfMap => write!(f, "plane would fall off the map"),
+            &Overlap { ref with } => {
+                let ids: Vec<String> = with.iter().map(|id| id.to_string()).collect();
+                write!(f, "plane would overlap with: {}", ids.join(", "))
+            },
+            &GameAlreadyStarted => write!(f, "cannot add planes mid-game"),
+            &TooManyPlanes => write!(f, "all planes are already on the board"),
+        }
+    }
+}
+
+impl Error for PlacementError { }
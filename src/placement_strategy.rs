@@ -0,0 +1,204 @@
+use std::fmt;
+use std::slice;
+
+use rand;
+use rand::Rng;
+
+use board::Board;
+use board_spec::BoardSpec;
+use plane::Plane;
+use coordinate::Coordinate;
+use plane_shape::PlaneShape;
+use fleet::Fleet;
+use rules::DEFAULT_FLEET_SIZE;
+use shot_history::ShotHistory;
+use bombardment_strategy::all_placements;
+
+/// How many times a layout is started over when the planes placed so far
+/// leave no room for the next one, before settling for a random layout.
+const MAX_ATTEMPTS: usize = 20;
+/// How many of the best placements the next plane is picked from, so that
+/// layouts keep some variety.
+const TOP_CHOICES: usize = 8;
+
+/// How an AI lays out its planes before the game starts.
+///
+/// Every strategy but `Random` places one plane after the other where it
+/// scores best among the spots left, and falls back to a random layout when
+/// it paints itself into a corner, so a layout is always found.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "snake_case"))]
+pub enum PlacementStrategy {
+    /// Uniformly random heads and orientations.
+    Random,
+    /// Keeps the planes along the sides of the map, which hunting shots
+    /// reach last.
+    EdgeHugging,
+    /// Keeps the planes as far from each other as possible, so that finding
+    /// one gives nothing away about the others.
+    SpreadOut,
+    /// Keeps the planes off the tiles bombarded most often in the history,
+    /// or off those a hunting AI tries first while there is none.
+    AntiHeatmap(ShotHistory),
+    /// Sets out a lone decoy in the middle of the map, where hunting starts,
+    /// and packs the other planes together far away from it.
+    ClusteredDecoy,
+}
+
+impl PlacementStrategy {
+    pub fn new(from: &str) -> Option<PlacementStrategy> {
+        use PlacementStrategy::*;
+        match from {
+            "random" => Some(Random),
+            "edge_hugging" => Some(EdgeHugging),
+            "spread_out" => Some(SpreadOut),
+            "anti_heatmap" => Some(AntiHeatmap(ShotHistory::default())),
+            "clustered_decoy" => Some(ClusteredDecoy),
+            _ => None,
+        }
+    }
+    pub fn new_layout(&self) -> Board {
+        self.new_layout_with_rng(&mut rand::thread_rng())
+    }
+    pub fn new_layout_with_rng<R: Rng>(&self, rng: &mut R) -> Board {
+        self.new_layout_with_fleet(&BoardSpec::default(), DEFAULT_FLEET_SIZE, rng)
+    }
+    /// A layout of `fleet_size` classic planes on a map of the size `spec`.
+    pub fn new_layout_with_fleet<R: Rng>(&self, spec: &BoardSpec, fleet_size: usize, rng: &mut R) -> Board {
+        self.new_layout_with_shape(spec, fleet_size, PlaneShape::default(), rng)
+    }
+    pub fn new_layout_with_shape<R: Rng>(&self, spec: &BoardSpec, fleet_size: usize, shape: PlaneShape, rng: &mut R) -> Board {
+        self.new_layout_with_mixed_fleet(spec, Fleet::new_uniform(fleet_size, shape), rng)
+    }
+    pub fn new_layout_with_mixed_fleet<R: Rng>(&self, spec: &BoardSpec, fleet: Fleet, rng: &mut R) -> Board {
+        use PlacementStrategy::*;
+        match self {
+            &Random => Board::new_random_with_mixed_fleet(spec, fleet, rng),
+            _ => match self.new_scored_layout(spec, &fleet, rng) {
+                Some(layout) => layout,
+                None => Board::new_random_with_mixed_fleet(spec, fleet, rng),
+            },
+        }
+    }
+    fn new_scored_layout<R: Rng>(&self, spec: &BoardSpec, fleet: &Fleet, rng: &mut R) -> Option<Board> {
+        let weights = match self {
+            &PlacementStrategy::AntiHeatmap(ref history) => tile_weights(spec, fleet, history),
+            _ => Vec::new(),
+        };
+        for _ in 0..MAX_ATTEMPTS {
+            let mut layout = Board::new_with_mixed_fleet(*spec, fleet.clone());
+            while let Some(shape) = layout.next_shape().cloned() {
+                let mut candidates: Vec<Plane> = all_placements(spec, &shape).into_iter()
+                    .filter(|plane| !layout.planes().iter().any(|placed| plane.is_overlapping_with(placed)))
+                    .collect();
+                if candidates.is_empty() {
+                    break;
+                }
+                // Shuffled first, so that the stable sort breaks ties randomly.
+                rng.shuffle(&mut candidates);
+                let mut scored: Vec<(f64, Plane)> = candidates.into_iter()
+                    .map(|plane| (self.score(&plane, layout.planes(), spec, &weights), plane))
+                    .collect();
+                scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+                let choices = scored.len().min(TOP_CHOICES);
+                let (_, plane) = scored.swap_remove(rng.gen_range(0, choices));
+                if layout.add_new_plane(*plane.head(), plane.orientation().clone()).is_err() {
+                    break;
+                }
+            }
+            if layout.is_initialized() {
+                return Some(layout);
+            }
+        }
+        None
+    }
+    /// How good a spot `plane` is given the planes already `placed`, the
+    /// higher the better.
+    fn score(&self, plane: &Plane, placed: &[Plane], spec: &BoardSpec, weights: &[f64]) -> f64 {
+        use PlacementStrategy::*;
+        let tiles = tiles_of(plane, spec);
+        match self {
+            &Random => 0.0,
+            &EdgeHugging => {
+                let to_edge = |&(x, y): &(usize, usize)| x.min(y).min(spec.width - 1 - x).min(spec.height - 1 - y);
+                -(tiles.iter().map(to_edge).sum::<usize>() as f64)
+            },
+            &SpreadOut => distance(&tiles, placed, spec) as f64,
+            &AntiHeatmap(_) => -tiles.iter().map(|&(x, y)| weights[y * spec.width + x]).sum::<f64>(),
+            &ClusteredDecoy => match placed.split_first() {
+                None => {
+                    let (x, y) = tiles[0];
+                    let from_middle = |at: usize, side: usize| (at as f64 - (side - 1) as f64 / 2.0).abs();
+                    -(from_middle(x, spec.width) + from_middle(y, spec.height))
+                },
+                Some((decoy, cluster)) => {
+                    let from_cluster = match cluster.is_empty() {
+                        true => 0,
+                        false => distance(&tiles, cluster, spec),
+                    };
+                    distance(&tiles, slice::from_ref(decoy), spec) as f64 - 2.0 * from_cluster as f64
+                },
+            },
+        }
+    }
+}
+
+impl fmt::Display for PlacementStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PlacementStrategy::*;
+        write!(f, "{}", match self {
+            &Random => "random",
+            &EdgeHugging => "edge_hugging",
+            &SpreadOut => "spread_out",
+            &AntiHeatmap(_) => "anti_heatmap",
+            &ClusteredDecoy => "clustered_decoy",
+        })
+    }
+}
+
+/// The head and body tiles of a plane on the map, head first, as `(x, y)`.
+fn tiles_of(plane: &Plane, spec: &BoardSpec) -> Vec<(usize, usize)> {
+    let mut tiles = vec![plane.head().as_tuple()];
+    tiles.extend(plane.coordinate_iterator_on(spec).filter_map(|tile| tile).map(|tile| tile.as_tuple()));
+    tiles
+}
+
+/// The smallest number of steps from one of the `tiles` to one of the tiles
+/// of the `planes`.
+fn distance(tiles: &[(usize, usize)], planes: &[Plane], spec: &BoardSpec) -> usize {
+    let steps = |a: usize, b: usize| a.max(b) - a.min(b);
+    planes.iter()
+        .flat_map(|plane| tiles_of(plane, spec))
+        .flat_map(|(other_x, other_y)| tiles.iter().map(move |&(x, y)| steps(x, other_x) + steps(y, other_y)))
+        .min()
+        .unwrap_or(0)
+}
+
+/// How much each tile, by index, is to be avoided: how often it was shot at
+/// in the history, or its `coverage` when the history is empty or of another
+/// map, which is what hunting goes after.
+fn tile_weights(spec: &BoardSpec, fleet: &Fleet, history: &ShotHistory) -> Vec<f64> {
+    if history.spec() == spec && history.total() > 0 {
+        return (0..spec.tile_count())
+            .map(|index| history.count(&Coordinate::new_from_usize(index, spec)) as f64)
+            .collect();
+    }
+    coverage(spec, fleet)
+}
+
+/// For each tile, by index, in how many ways a single plane of the fleet can
+/// cover it: the middle of the map the most, the corners the least.
+pub fn coverage(spec: &BoardSpec, fleet: &Fleet) -> Vec<f64> {
+    let mut coverage = vec![0.0; spec.tile_count()];
+    for shape in fleet.distinct_shapes() {
+        for plane in all_placements(spec, shape) {
+            for (x, y) in tiles_of(&plane, spec) {
+                coverage[y * spec.width + x] += 1.0;
+            }
+        }
+    }
+    coverage
+}
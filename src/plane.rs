@@ -1,33 +1,31 @@
 use coordinate::Coordinate;
 use orientation::Orientation;
+use plane_shape::PlaneShape;
+use board_spec::{BoardSpec, MAX_SIZE};
 
+/// The tiles of a plane's body, `None` for those off the map.
 #[derive(Clone)]
 pub struct PlanePositionIterator<'a> {
     head: &'a Coordinate,
     orientation: &'a Orientation,
+    shape: &'a PlaneShape,
     current_tile: usize,
+    spec: BoardSpec,
 }
 
 impl<'a> Iterator for PlanePositionIterator<'a> {
     type Item = Option<Coordinate>;
     fn next(&mut self) -> Option<Option<Coordinate>> {
-        use Orientation::*;
-        if self.current_tile > 8 {
-            return None;
-        }
-        let tiles = match self.orientation {
-            &North => vec![(-2,  1), (-1,  1), ( 0,  1), ( 1,  1), ( 2,  1), ( 0,  2), (-1,  3), ( 0,  3), ( 1,  3)],
-            &South => vec![( 2, -1), ( 1, -1), ( 0, -1), (-1, -1), (-2, -1), ( 0, -2), ( 1, -3), ( 0, -3), (-1, -3)],
-            &East =>  vec![(-1, -2), (-1, -1), (-1,  0), (-1,  1), (-1,  2), (-2,  0), (-3, -1), (-3,  0), (-3,  1)],
-            &West =>  vec![( 1,  2), ( 1,  1), ( 1,  0), ( 1, -1), ( 1, -2), ( 2,  0), ( 3,  1), ( 3,  0), ( 3, -1)],
+        let (move_x, move_y) = match self.shape.body_offset(self.current_tile, self.orientation) {
+            Some(offset) => offset,
+            None => return None,
         };
-        let move_x = tiles[self.current_tile].0;
-        let move_y = tiles[self.current_tile].1;
         self.current_tile += 1;
-        let t = self.head.new_moved_by(move_x, move_y);
+        let t = self.head.new_moved_by(move_x, move_y).filter(|coord| self.spec.contains(coord));
         Some(t)
     }
 }
+/// The tile indices of a plane's body, skipping the tiles off the map.
 pub struct TileIterator<'a> {
     coordinate_iterator: PlanePositionIterator<'a>,
 }
@@ -40,7 +38,7 @@ impl<'a> Iterator for TileIterator<'a> {
                 Some(maybe_coord) => {
                     match maybe_coord {
                         Some(coord) => {
-                            return Some(coord.as_usize());
+                            return Some(coord.as_usize(&self.coordinate_iterator.spec));
                         },
                         None => continue,
                     }
@@ -52,10 +50,15 @@ impl<'a> Iterator for TileIterator<'a> {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Plane {
     head: Coordinate,
     orientation: Orientation,
     id: usize,
+    /// Planes saved before shapes could change are classic ones, and
+    /// classic planes are still saved without their shape.
+    #[cfg_attr(feature = "serialize", serde(default, skip_serializing_if = "PlaneShape::is_classic"))]
+    shape: PlaneShape,
 }
 
 impl Plane {
@@ -64,24 +67,38 @@ impl Plane {
     }
     pub fn new_with_id(from: &str, orientation: &str, id: usize) -> Option<Plane> {
         match (Coordinate::new(from), Orientation::new(orientation)) {
-            (Some(c), Some(o)) => Some(Plane {
-                head: c,
-                orientation: o,
-                id: id,
-            }),
+            (Some(c), Some(o)) => Some(Plane::new_from_parts(c, o, id)),
             _ => None,
         }
     }
+    pub fn new_from_parts(head: Coordinate, orientation: Orientation, id: usize) -> Plane {
+        Plane::new_with_shape(head, orientation, PlaneShape::default(), id)
+    }
+    pub fn new_with_shape(head: Coordinate, orientation: Orientation, shape: PlaneShape, id: usize) -> Plane {
+        Plane {
+            head: head,
+            orientation: orientation,
+            id: id,
+            shape: shape,
+        }
+    }
+    /// The tiles of the body on the largest possible map. Those of a plane
+    /// placed on a board are all on its map.
     pub fn coordinate_iterator(&self) -> PlanePositionIterator {
+        self.coordinate_iterator_on(&BoardSpec { width: MAX_SIZE, height: MAX_SIZE })
+    }
+    pub fn coordinate_iterator_on(&self, spec: &BoardSpec) -> PlanePositionIterator {
         PlanePositionIterator {
             head: &self.head,
             orientation: &self.orientation,
+            shape: &self.shape,
             current_tile: 0,
+            spec: *spec,
         }
     }
-    pub fn tile_iterator(&self) -> TileIterator {
+    pub fn tile_iterator(&self, spec: &BoardSpec) -> TileIterator {
         TileIterator {
-            coordinate_iterator: self.coordinate_iterator(),
+            coordinate_iterator: self.coordinate_iterator_on(spec),
         }
     }
     pub fn orientation(&self) -> &Orientation {
@@ -90,8 +107,11 @@ impl Plane {
     pub fn head(&self) -> &Coordinate {
         &self.head
     }
-    pub fn is_outside_of_map(&self) -> bool {
-        self.coordinate_iterator().find(|x: &Option<Coordinate>| *x == None) == Some(None)
+    pub fn shape(&self) -> &PlaneShape {
+        &self.shape
+    }
+    pub fn is_outside_of_map(&self, spec: &BoardSpec) -> bool {
+        !spec.contains(&self.head) || self.coordinate_iterator_on(spec).any(|tile| tile.is_none())
     }
     pub fn is_overlapping_with(&self, other: &Plane) -> bool {
         if self.head == other.head {
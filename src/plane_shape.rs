@@ -0,0 +1,157 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use board_spec::{BoardSpec, MAX_SIZE};
+use orientation::Orientation;
+
+/// The silhouette of a plane, as the offsets of its body tiles from its head
+/// when it flies north: `x` grows to the east and `y` to the south, so the
+/// body sits at positive `y`. The other orientations are rotations of it.
+///
+/// Shapes are read back from JSON through `new`, see `serialize.rs`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct PlaneShape {
+    name: String,
+    body: Vec<(i32, i32)>,
+}
+
+impl PlaneShape {
+    /// A shape with at least one body tile, none of them on the head or
+    /// twice in the list, which fits on the largest map.
+    pub fn new(name: &str, body: Vec<(i32, i32)>) -> Option<PlaneShape> {
+        if body.is_empty() || body.contains(&(0, 0)) {
+            return None;
+        }
+        // Farther offsets never fit, and would overflow when rotated.
+        let is_near = |offset: i32| offset > -(MAX_SIZE as i32) && offset < MAX_SIZE as i32;
+        if !body.iter().all(|&(x, y)| is_near(x) && is_near(y)) {
+            return None;
+        }
+        if body.iter().enumerate().any(|(i, offset)| body[i+1..].contains(offset)) {
+            return None;
+        }
+        let shape = PlaneShape {
+            name: name.to_string(),
+            body: body,
+        };
+        match shape.fits_on(&BoardSpec { width: MAX_SIZE, height: MAX_SIZE }) {
+            true => Some(shape),
+            false => None,
+        }
+    }
+    /// Reads a shape drawn flying north, one line per row: `^` marks the
+    /// head, `o` a body tile, and anything else an empty tile, e.g.
+    ///
+    /// ```text
+    ///  ^
+    /// ooo
+    ///  o
+    /// ```
+    pub fn new_from_drawing(name: &str, drawing: &str) -> Option<PlaneShape> {
+        let mut head = None;
+        let mut tiles = Vec::new();
+        for (y, line) in drawing.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '^' if head.is_some() => return None,
+                    '^' => head = Some((x as i32, y as i32)),
+                    'o' => tiles.push((x as i32, y as i32)),
+                    _ => { },
+                }
+            }
+        }
+        let (head_x, head_y) = match head {
+            Some(head) => head,
+            None => return None,
+        };
+        PlaneShape::new(name, tiles.into_iter().map(|(x, y)| (x - head_x, y - head_y)).collect())
+    }
+    /// Reads a drawing as in `new_from_drawing` from a file, named after it.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> io::Result<PlaneShape> {
+        let mut drawing = String::new();
+        File::open(path.as_ref())?.read_to_string(&mut drawing)?;
+        let name = path.as_ref().file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        PlaneShape::new_from_drawing(&name, &drawing)
+            .ok_or(io::Error::new(io::ErrorKind::InvalidData, "not a plane shape: it needs one head ^, at least one body tile o, and to fit on the largest map"))
+    }
+    /// One of the built-in shapes: `classic`, `fighter` or `bomber`.
+    pub fn new_named(name: &str) -> Option<PlaneShape> {
+        match name {
+            "classic" => Some(PlaneShape::classic()),
+            "fighter" => Some(PlaneShape::fighter()),
+            "bomber" => Some(PlaneShape::bomber()),
+            _ => None,
+        }
+    }
+    /// The plane of the original game: five tiles of wings, a fuselage tile
+    /// and three tiles of tail.
+    pub fn classic() -> PlaneShape {
+        PlaneShape {
+            name: "classic".to_string(),
+            body: vec![(-2, 1), (-1, 1), (0, 1), (1, 1), (2, 1), (0, 2), (-1, 3), (0, 3), (1, 3)],
+        }
+    }
+    /// A small plane of five tiles.
+    pub fn fighter() -> PlaneShape {
+        PlaneShape {
+            name: "fighter".to_string(),
+            body: vec![(-1, 1), (0, 1), (1, 1), (0, 2)],
+        }
+    }
+    /// A large plane of fifteen tiles.
+    pub fn bomber() -> PlaneShape {
+        PlaneShape {
+            name: "bomber".to_string(),
+            body: vec![(-3, 1), (-2, 1), (-1, 1), (0, 1), (1, 1), (2, 1), (3, 1), (0, 2), (0, 3), (-2, 4), (-1, 4), (0, 4), (1, 4), (2, 4)],
+        }
+    }
+    pub fn is_classic(&self) -> bool {
+        *self == PlaneShape::classic()
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The offsets of the body tiles from the head when flying north.
+    pub fn body(&self) -> &Vec<(i32, i32)> {
+        &self.body
+    }
+    /// The width and the height of the smallest rectangle around the plane,
+    /// head included, when flying north.
+    pub fn extent(&self) -> (usize, usize) {
+        let xs = self.body.iter().map(|&(x, _)| x).chain(Some(0));
+        let ys = self.body.iter().map(|&(_, y)| y).chain(Some(0));
+        let span = |values: Vec<i32>| (*values.iter().max().unwrap() as i64 - *values.iter().min().unwrap() as i64) as usize + 1;
+        (span(xs.collect()), span(ys.collect()))
+    }
+    /// Whether the plane fits on the map flying at least one way.
+    pub fn fits_on(&self, spec: &BoardSpec) -> bool {
+        let (width, height) = self.extent();
+        (width <= spec.width && height <= spec.height) || (height <= spec.width && width <= spec.height)
+    }
+    /// How many tiles the plane covers, its head included.
+    pub fn tile_count(&self) -> usize {
+        self.body.len() + 1
+    }
+    /// The offset from the head of the body tile number `index` when flying
+    /// towards `orientation`.
+    pub fn body_offset(&self, index: usize, orientation: &Orientation) -> Option<(i32, i32)> {
+        use Orientation::*;
+        self.body.get(index).map(|&(x, y)| match orientation {
+            &North => (x, y),
+            &South => (-x, -y),
+            &East => (-y, x),
+            &West => (y, -x),
+        })
+    }
+}
+
+impl Default for PlaneShape {
+    fn default() -> PlaneShape {
+        PlaneShape::classic()
+    }
+}
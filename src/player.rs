@@ -0,0 +1,26 @@
+use game::Game;
+use coordinate::Coordinate;
+use orientation::Orientation;
+
+/// A controller deciding the moves of one player slot of a `Game`.
+///
+/// Controllers only ever propose moves; the `Game` validates and applies
+/// them. Returning `None` means that no move is ready yet, e.g. a human who
+/// has not submitted anything or a remote peer which is still thinking.
+pub trait Player {
+    /// Where the player `me` wants to put its next plane.
+    fn next_plane(&mut self, game: &Game, me: usize) -> Option<(Coordinate, Orientation)>;
+    /// Which tile the player `me` wants to bombard next.
+    fn next_target(&mut self, game: &Game, me: usize) -> Option<Coordinate>;
+}
+
+/// Lets a frontend lend a controller to an `Engine` and keep it afterwards,
+/// together with whatever state it built up.
+impl<'a, P: Player + ?Sized> Player for &'a mut P {
+    fn next_plane(&mut self, game: &Game, me: usize) -> Option<(Coordinate, Orientation)> {
+        (**self).next_plane(game, me)
+    }
+    fn next_target(&mut self, game: &Game, me: usize) -> Option<Coordinate> {
+        (**self).next_target(game, me)
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(feature = "serialize")]
+use std::fs::File;
+#[cfg(feature = "serialize")]
+use std::io;
+#[cfg(feature = "serialize")]
+use std::io::{Read, Write};
+#[cfg(feature = "serialize")]
+use std::path::Path;
+
+#[cfg(feature = "serialize")]
+use serde_json;
+
+use game::Game;
+use layout_history::LayoutHistory;
+use shot_history::ShotHistory;
+
+/// What the AI remembers of a player from one game to the next: where they
+/// put their planes, for the `Adaptive` level to look there first, and where
+/// they fired, for the AI to keep its own planes elsewhere.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct PlayerStats {
+    pub layouts: LayoutHistory,
+    pub shots: ShotHistory,
+}
+
+impl PlayerStats {
+    pub fn new() -> PlayerStats {
+        PlayerStats::default()
+    }
+    /// Records the layout of `player` and the shots they fired. A game on a
+    /// map of another size starts the stats over.
+    pub fn record_game(&mut self, game: &Game, player: usize) {
+        let board = &game.players[player].board;
+        if board.spec() != self.shots.spec() {
+            self.shots = ShotHistory::new(*board.spec());
+        }
+        self.layouts.record(board);
+        // The shots fired at the others, who may be defeated by now.
+        for opponent in (0..game.players.len()).filter(|&other| other != player) {
+            self.shots.record_game(game, opponent);
+        }
+    }
+
+    #[cfg(feature = "serialize")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("game types always serialize")
+    }
+    #[cfg(feature = "serialize")]
+    pub fn from_json(json: &str) -> Result<PlayerStats, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+    #[cfg(feature = "serialize")]
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        File::create(path)?.write_all(self.to_json().as_bytes())
+    }
+    /// Reads stats written by `save_to`; a missing file is a player never
+    /// seen before.
+    #[cfg(feature = "serialize")]
+    pub fn load_from<P: AsRef<Path>>(path: P) -> io::Result<PlayerStats> {
+        let mut json = String::new();
+        match File::open(path) {
+            Ok(mut file) => file.read_to_string(&mut json)?,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(PlayerStats::new()),
+            Err(error) => return Err(error),
+        };
+        PlayerStats::from_json(&json).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::VecDeque;
+
+use player::Player;
+use game::Game;
+use coordinate::Coordinate;
+use orientation::Orientation;
+
+/// A player whose moves are pushed in from the outside, e.g. by a human
+/// through one of the frontends, or by a remote peer.
+#[derive(Clone)]
+pub struct QueuedPlayer {
+    planes: VecDeque<(Coordinate, Orientation)>,
+    targets: VecDeque<Coordinate>,
+}
+
+impl QueuedPlayer {
+    pub fn new() -> QueuedPlayer {
+        QueuedPlayer {
+            planes: VecDeque::new(),
+            targets: VecDeque::new(),
+        }
+    }
+    pub fn push_plane(&mut self, head: Coordinate, orientation: Orientation) {
+        self.planes.push_back((head, orientation));
+    }
+    pub fn push_target(&mut self, target: Coordinate) {
+        self.targets.push_back(target);
+    }
+}
+
+impl Player for QueuedPlayer {
+    fn next_plane(&mut self, _game: &Game, _me: usize) -> Option<(Coordinate, Orientation)> {
+        self.planes.pop_front()
+    }
+    fn next_target(&mut self, _game: &Game, _me: usize) -> Option<Coordinate> {
+        self.targets.pop_front()
+    }
+}
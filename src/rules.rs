@@ -0,0 +1,96 @@
+use board::Board;
+use board_spec::BoardSpec;
+use fleet::Fleet;
+use plane_shape::PlaneShape;
+
+/// How many planes each player places unless the rules say otherwise.
+pub const DEFAULT_FLEET_SIZE: usize = 3;
+
+/// What a game is played with: the size of the map, and the fleet each
+/// player places, one entry per player slot. Fleets may differ, e.g. to give
+/// a weaker player a head start.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Rules {
+    pub spec: BoardSpec,
+    pub fleets: Vec<Fleet>,
+}
+
+impl Rules {
+    /// Rules for at least two players flying classic planes, each with a
+    /// fleet which fits on the map, see `max_fleet_size`.
+    pub fn new(spec: BoardSpec, fleet_sizes: Vec<usize>) -> Option<Rules> {
+        Rules::new_with_shape(spec, fleet_sizes, PlaneShape::default())
+    }
+    pub fn new_with_shape(spec: BoardSpec, fleet_sizes: Vec<usize>, shape: PlaneShape) -> Option<Rules> {
+        Rules::new_with_fleets(spec, fleet_sizes.into_iter().map(|size| Fleet::new_uniform(size, shape.clone())).collect())
+    }
+    /// Rules for at least two players, each with a fleet of at least one
+    /// plane, whose planes cover at most half of the map so that a random
+    /// layout is always found quickly, and each fit on it. Whether the whole
+    /// fleet can be laid out may take a search, see `can_be_laid_out`.
+    pub fn new_with_fleets(spec: BoardSpec, fleets: Vec<Fleet>) -> Option<Rules> {
+        let fits = |fleet: &Fleet| {
+            !fleet.is_empty()
+                && fleet.tile_count() <= spec.tile_count() / 2
+                && fleet.shapes().iter().all(|shape| shape.fits_on(&spec))
+        };
+        match fleets.len() >= 2 && fleets.iter().all(fits) {
+            true => Some(Rules { spec: spec, fleets: fleets }),
+            false => None,
+        }
+    }
+    /// Every one of `player_count` players places `fleet_size` planes.
+    pub fn new_even(spec: BoardSpec, player_count: usize, fleet_size: usize) -> Option<Rules> {
+        Rules::new(spec, vec![fleet_size; player_count])
+    }
+    /// Reads the fleets of a two player game: `3` for three planes of the
+    /// `shape` each, `3:2` for three planes for the first player and two for
+    /// the second, and mixed fleets like `1 bomber, 2 fighters` or
+    /// `1 bomber, 2 fighters:3 fighters`, see `Fleet::new_from_str`.
+    pub fn new_from_fleet_str(spec: BoardSpec, shape: PlaneShape, from: &str) -> Option<Rules> {
+        let fleets: Vec<Option<Fleet>> = from.split(':').map(|fleet| match fleet.trim().parse::<usize>() {
+            Ok(size) => Some(Fleet::new_uniform(size, shape.clone())),
+            Err(_) => Fleet::new_from_str(fleet),
+        }).collect();
+        match fleets.as_slice() {
+            &[Some(ref fleet)] => Rules::new_with_fleets(spec, vec![fleet.clone(), fleet.clone()]),
+            &[Some(ref first), Some(ref second)] => Rules::new_with_fleets(spec, vec![first.clone(), second.clone()]),
+            _ => None,
+        }
+    }
+    /// The largest fleet of `shape` planes allowed on the map.
+    pub fn max_fleet_size(spec: &BoardSpec, shape: &PlaneShape) -> usize {
+        spec.tile_count() / 2 / shape.tile_count()
+    }
+    /// Whether every fleet can be laid out on the map. Fleets which fit side
+    /// by side are, the others are searched for with
+    /// `Board::new_first_fit_with_mixed_fleet`, which may take a while on
+    /// crowded maps: check once when the fleets are chosen.
+    pub fn can_be_laid_out(&self) -> bool {
+        self.fleets.iter().enumerate().all(|(index, fleet)| {
+            self.fleets[..index].contains(fleet)
+                || fleet.fits_side_by_side(&self.spec)
+                || Board::new_first_fit_with_mixed_fleet(&self.spec, fleet.clone()).is_some()
+        })
+    }
+    pub fn player_count(&self) -> usize {
+        self.fleets.len()
+    }
+    /// The fleet `player` bombards while the game is young: the one of the
+    /// next player in turn order.
+    pub fn opponent_fleet(&self, player: usize) -> &Fleet {
+        &self.fleets[(player + 1) % self.player_count()]
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            spec: BoardSpec::default(),
+            fleets: vec![Fleet::default(); 2],
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::error::Error;
+
+use serde_json;
+
+use game::Game;
+use board::Board;
+use ai_level::AiLevel;
+use ai_player::AiPlayer;
+use layout_history::LayoutHistory;
+
+/// Bumped whenever the layout of `SavedGame` changes in a way older
+/// versions of the program cannot read.
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+/// A game against the AI, frozen so that it can be resumed later or shared
+/// to reproduce a position.
+///
+/// On disk, this is a JSON object like
+/// `{"version": 1, "game": ..., "ai_level": "advanced", "ai_layout": ...}`,
+/// with the shapes of `Game` and `Board` described in `serialize.rs`.
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub game: Game,
+    pub ai_level: AiLevel,
+    /// The planes the AI is going to place, generated when the game started.
+    pub ai_layout: Board,
+    /// What the AI remembered of its opponent, absent from older saves.
+    #[serde(default)]
+    pub ai_history: LayoutHistory,
+}
+
+impl SavedGame {
+    pub fn new(game: &Game, ai: &AiPlayer) -> SavedGame {
+        SavedGame {
+            version: SAVE_FORMAT_VERSION,
+            game: game.clone(),
+            ai_level: ai.level(),
+            ai_layout: ai.layout().clone(),
+            ai_history: ai.history().clone(),
+        }
+    }
+    /// An AI picking up where the saved one left off. Its random generator
+    /// is not saved, so it does not necessarily fire the same shots.
+    pub fn ai_player(&self) -> AiPlayer {
+        AiPlayer::new_with_history(self.ai_layout.clone(), self.ai_level, self.ai_history.clone())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("game types always serialize")
+    }
+    pub fn from_json(json: &str) -> Result<SavedGame, SaveError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        match value.get("version").and_then(|version| version.as_u64()) {
+            Some(version) if version == SAVE_FORMAT_VERSION as u64 => { },
+            Some(version) => return Err(SaveError::UnsupportedVersion(version)),
+            None => return Err(SaveError::Format("missing version".to_string())),
+        }
+        let saved: SavedGame = serde_json::from_value(value)?;
+        saved.ai_layout.validate().map_err(SaveError::Format)?;
+        Ok(saved)
+    }
+
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        let mut file = File::create(path)?;
+        file.write_all(self.to_json().as_bytes())?;
+        Ok(())
+    }
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<SavedGame, SaveError> {
+        let mut json = String::new();
+        File::open(path)?.read_to_string(&mut json)?;
+        SavedGame::from_json(&json)
+    }
+}
+
+/// Why a `SavedGame` could not be written or read back.
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// The file is not a saved game.
+    Format(String),
+    /// The file was saved by a version of the program using another format.
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use SaveError::*;
+        match self {
+            &Io(ref error) => write!(f, "{}", error),
+            &Format(ref reason) => write!(f, "not a saved game: {}", reason),
+            &UnsupportedVersion(version) => write!(f, "unsupported save format version {}, expected {}", version, SAVE_FORMAT_VERSION),
+        }
+    }
+}
+
+impl Error for SaveError { }
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> SaveError {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> SaveError {
+        SaveError::Format(error.to_string())
+    }
+}
@@ -0,0 +1,175 @@
+//! Serde support, enabled by the `serialize` cargo feature.
+//!
+//! The JSON shape is meant to be stable, so that saved games and API clients
+//! keep working across versions:
+//!
+//! * a `Coordinate` is a string like `"A5"`, an `Orientation` one of `"N"`,
+//!   `"E"`, `"S"` and `"W"`
+//! * a `PlaneShape` is `{"name": "fighter", "body": [[-1, 1], [0, 1], [1,
+//!   1], [0, 2]]}`, the offsets of the body from the head when flying north,
+//!   checked like those of a drawn shape
+//! * a `Plane` is `{"head": "E5", "orientation": "N", "id": 1, "shape":
+//!   ...}`, where `shape` may be left out for a classic plane
+//! * a `Board` lists its `planes`, `killed_planes`, `hits`, `misses` and
+//!   `kills`, plus the tile numbers (`width * row + column`) which were not
+//!   bombarded yet as `empty_indices`, its `spec`, `{"width": 10,
+//!   "height": 10}`, which may be left out for a 10x10 map and is checked
+//!   like the sizes given on the command line, and its `fleet`, the list of
+//!   the shapes of its planes, three classic ones if left out
+//! * a `GamePlay` is `{"PlaceNewPlane": 0}`, `{"Bombard": 1}` or
+//!   `{"Won": 0}`, keyed by the player slot
+//! * a `BombardmentResult` is one of `"Hit"`, `"Miss"`, `"Retry"` and
+//!   `{"Kill": "fighter"}`, with the shape of the killed plane
+//! * a `PlacementError` is `"OutOfMap"`, `{"Overlap": {"with": [1, 2]}}`,
+//!   `{"InvalidCoordinate": "K1"}` and so on
+//! * a `Move` is `{"Placed": {"player": 0, "head": "E5", "orientation":
+//!   "N"}}`, `{"Bombarded": {"player": 1, "target": "B7", "result":
+//!   "Miss"}}` or `{"StateChanged": {"Bombard": 0}}`
+//! * a `Game` is `{"gameplay": ..., "players": [{"board": ..., "scrapbook":
+//!   ...}, ...], "reveal_killed": false, "moves": [...]}`, where `moves`
+//!   may be left out; everything in it is checked to be on the map and of
+//!   its players, see `Game::validate`
+//! * a `LayoutHistory` is `{"spec": ..., "games": 2, "heads": [...],
+//!   "bodies": [...]}` and a `ShotHistory` `{"spec": ..., "counts": [...]}`,
+//!   checked to hold one count per tile of their map
+use std::fmt;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor};
+
+use coordinate::Coordinate;
+use orientation::Orientation;
+use plane_shape::PlaneShape;
+use board_spec::{BoardSpec, MIN_SIZE, MAX_SIZE};
+use game::{Game, PlayerSlot};
+use game_play::GamePlay;
+use move_log::Move;
+use layout_history::LayoutHistory;
+use shot_history::ShotHistory;
+
+impl Serialize for Coordinate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Coordinate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Coordinate, D::Error> {
+        deserializer.deserialize_str(ParsedStr("a coordinate like A5", Coordinate::new))
+    }
+}
+
+impl Serialize for Orientation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Orientation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Orientation, D::Error> {
+        deserializer.deserialize_str(ParsedStr("one of N, E, S, W", Orientation::new))
+    }
+}
+
+/// The fields of a `BoardSpec`, before `BoardSpec::new` checks them.
+#[derive(Deserialize)]
+struct RawBoardSpec {
+    width: usize,
+    height: usize,
+}
+
+impl<'de> Deserialize<'de> for BoardSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BoardSpec, D::Error> {
+        let raw = RawBoardSpec::deserialize(deserializer)?;
+        BoardSpec::new(raw.width, raw.height)
+            .ok_or_else(|| de::Error::custom(format!("not a map size: sides go from {} to {}", MIN_SIZE, MAX_SIZE)))
+    }
+}
+
+/// The fields of a `Game`, before `Game::validate` checks them.
+#[derive(Deserialize)]
+struct RawGame {
+    gameplay: GamePlay,
+    players: Vec<PlayerSlot>,
+    reveal_killed: bool,
+    #[serde(default)]
+    moves: Vec<Move>,
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Game, D::Error> {
+        let raw = RawGame::deserialize(deserializer)?;
+        let game = Game {
+            gameplay: raw.gameplay,
+            players: raw.players,
+            reveal_killed: raw.reveal_killed,
+            moves: raw.moves,
+        };
+        match game.validate() {
+            Ok(()) => Ok(game),
+            Err(reason) => Err(de::Error::custom(format!("not a game: {}", reason))),
+        }
+    }
+}
+
+/// The fields of a `PlaneShape`, before `PlaneShape::new` checks them.
+#[derive(Deserialize)]
+struct RawPlaneShape {
+    name: String,
+    body: Vec<(i32, i32)>,
+}
+
+impl<'de> Deserialize<'de> for PlaneShape {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<PlaneShape, D::Error> {
+        let raw = RawPlaneShape::deserialize(deserializer)?;
+        PlaneShape::new(&raw.name, raw.body)
+            .ok_or_else(|| de::Error::custom("not a plane shape: it needs at least one body tile, none on the head or twice, and to fit on the largest map"))
+    }
+}
+
+/// The fields of a `LayoutHistory`, before `LayoutHistory::new_from_counts`
+/// checks them.
+#[derive(Deserialize)]
+struct RawLayoutHistory {
+    spec: BoardSpec,
+    games: usize,
+    heads: Vec<usize>,
+    bodies: Vec<usize>,
+}
+
+impl<'de> Deserialize<'de> for LayoutHistory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LayoutHistory, D::Error> {
+        let raw = RawLayoutHistory::deserialize(deserializer)?;
+        LayoutHistory::new_from_counts(raw.spec, raw.games, raw.heads, raw.bodies)
+            .ok_or_else(|| de::Error::custom("not a layout history: it needs one count of heads and of bodies per tile of its map"))
+    }
+}
+
+/// The fields of a `ShotHistory`, before `ShotHistory::new_from_counts`
+/// checks them.
+#[derive(Deserialize)]
+struct RawShotHistory {
+    spec: BoardSpec,
+    counts: Vec<usize>,
+}
+
+impl<'de> Deserialize<'de> for ShotHistory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ShotHistory, D::Error> {
+        let raw = RawShotHistory::deserialize(deserializer)?;
+        ShotHistory::new_from_counts(raw.spec, raw.counts)
+            .ok_or_else(|| de::Error::custom("not a shot history: it needs one count per tile of its map"))
+    }
+}
+
+/// Visits a string and parses it with the constructor the frontends use.
+struct ParsedStr<T>(&'static str, fn(&str) -> Option<T>);
+
+impl<'de, T> Visitor<'de> for ParsedStr<T> {
+    type Value = T;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        (self.1)(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
@@ -0,0 +1,70 @@
+use board_spec::BoardSpec;
+use bombardment_result::BombardmentResult;
+use coordinate::Coordinate;
+use game::Game;
+use move_log::Move;
+
+/// How often every tile of a map was bombarded over past games, e.g. by the
+/// humans playing against an AI, so that it can keep its planes elsewhere.
+/// Read back from JSON through `new_from_counts`, see `serialize.rs`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct ShotHistory {
+    spec: BoardSpec,
+    /// One count per tile index.
+    counts: Vec<usize>,
+}
+
+impl ShotHistory {
+    pub fn new(spec: BoardSpec) -> ShotHistory {
+        ShotHistory {
+            spec: spec,
+            counts: vec![0; spec.tile_count()],
+        }
+    }
+    /// A history with the shots fired at every tile, or `None` when there is
+    /// not one count per tile of the map.
+    pub fn new_from_counts(spec: BoardSpec, counts: Vec<usize>) -> Option<ShotHistory> {
+        match BoardSpec::new(spec.width, spec.height).is_some() && counts.len() == spec.tile_count() {
+            true => Some(ShotHistory { spec: spec, counts: counts }),
+            false => None,
+        }
+    }
+    pub fn spec(&self) -> &BoardSpec {
+        &self.spec
+    }
+    pub fn record(&mut self, target: &Coordinate) {
+        if self.spec.contains(target) {
+            self.counts[target.as_usize(&self.spec)] += 1;
+        }
+    }
+    /// Records every shot fired at `player` during a game played on a map
+    /// of the same size.
+    pub fn record_game(&mut self, game: &Game, player: usize) {
+        for played in &game.moves {
+            if let &Move::Bombarded { player: shooter, ref target, ref result } = played {
+                if shooter != player && *result != BombardmentResult::Retry {
+                    self.record(target);
+                }
+            }
+        }
+    }
+    pub fn count(&self, at: &Coordinate) -> usize {
+        match self.spec.contains(at) {
+            true => self.counts[at.as_usize(&self.spec)],
+            false => 0,
+        }
+    }
+    /// How many shots were recorded in all.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+impl Default for ShotHistory {
+    fn default() -> ShotHistory {
+        ShotHistory::new(BoardSpec::default())
+    }
+}
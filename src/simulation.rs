@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::thread;
+
+use rand::{SeedableRng, StdRng};
+
+use ai_level::AiLevel;
+use ai_player::AiPlayer;
+use bombardment_result::BombardmentResult;
+use bombardment_strategy::BombardmentStrategy;
+use engine::Engine;
+use game::Game;
+use game_play::GamePlay;
+use move_log::Move;
+use placement_strategy::PlacementStrategy;
+use player::Player;
+use rules::Rules;
+
+/// How many threads a `Simulation` plays on unless told otherwise.
+pub const DEFAULT_THREADS: usize = 4;
+
+/// An AI taking part in a simulation: how it bombards, and how it lays out
+/// its planes.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Contender {
+    pub bombardment: BombardmentStrategy,
+    pub placement: PlacementStrategy,
+}
+
+impl Contender {
+    pub fn new(bombardment: BombardmentStrategy, placement: PlacementStrategy) -> Contender {
+        Contender {
+            bombardment: bombardment,
+            placement: placement,
+        }
+    }
+    /// Plays with the strategies of an AI level.
+    pub fn new_with_level(level: AiLevel) -> Contender {
+        Contender::new(level.bombardment_strategy(), level.placement_strategy())
+    }
+    /// Reads an AI level like `advanced`, or strategies like
+    /// `density/random`, the bombardment first; placing planes randomly when
+    /// only the bombardment is given.
+    pub fn new_from_str(from: &str) -> Option<Contender> {
+        if let Some(level) = AiLevel::new(from) {
+            return Some(Contender::new_with_level(level));
+        }
+        let mut strategies = from.splitn(2, '/');
+        let bombardment = strategies.next().and_then(BombardmentStrategy::new);
+        let placement = match strategies.next() {
+            Some(placement) => PlacementStrategy::new(placement),
+            None => Some(PlacementStrategy::Random),
+        };
+        match (bombardment, placement) {
+            (Some(bombardment), Some(placement)) => Some(Contender::new(bombardment, placement)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Contender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.bombardment, self.placement)
+    }
+}
+
+/// How a simulated game ended.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct GameOutcome {
+    /// The seed the game was played with.
+    pub seed: usize,
+    /// The contender who won, 0 for the first one and 1 for the second one.
+    pub winner: usize,
+    /// How many tiles the winner bombarded.
+    pub shots: usize,
+}
+
+/// How one contender of a match-up fared.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Statistics {
+    pub contender: Contender,
+    pub wins: usize,
+    /// The share of the games won, from 0 to 1.
+    pub win_rate: f64,
+    /// Over the games won, `None` when there are none.
+    pub mean_shots_to_win: Option<f64>,
+    pub median_shots_to_win: Option<f64>,
+    /// How many games were won in how many shots.
+    pub histogram: BTreeMap<usize, usize>,
+}
+
+impl Statistics {
+    /// The statistics of `contender`, the contender number `index` in the
+    /// `outcomes`.
+    pub fn new(contender: Contender, index: usize, outcomes: &[GameOutcome]) -> Statistics {
+        let mut shots: Vec<usize> = outcomes.iter()
+            .filter(|outcome| outcome.winner == index)
+            .map(|outcome| outcome.shots)
+            .collect();
+        shots.sort();
+        let mut histogram = BTreeMap::new();
+        for &count in &shots {
+            *histogram.entry(count).or_insert(0) += 1;
+        }
+        let wins = shots.len();
+        Statistics {
+            contender: contender,
+            wins: wins,
+            win_rate: match outcomes.len() {
+                0 => 0.0,
+                games => wins as f64 / games as f64,
+            },
+            mean_shots_to_win: match wins {
+                0 => None,
+                _ => Some(shots.iter().sum::<usize>() as f64 / wins as f64),
+            },
+            median_shots_to_win: match wins {
+                0 => None,
+                _ if wins % 2 == 0 => Some((shots[wins / 2 - 1] + shots[wins / 2]) as f64 / 2.0),
+                _ => Some(shots[wins / 2] as f64),
+            },
+            histogram: histogram,
+        }
+    }
+}
+
+/// The games played between two contenders, summed up.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MatchUp {
+    pub games: usize,
+    pub first: Statistics,
+    pub second: Statistics,
+}
+
+impl MatchUp {
+    pub fn new(first: Contender, second: Contender, outcomes: &[GameOutcome]) -> MatchUp {
+        MatchUp {
+            games: outcomes.len(),
+            first: Statistics::new(first, 0, outcomes),
+            second: Statistics::new(second, 1, outcomes),
+        }
+    }
+}
+
+/// Plays many games between AIs to compare their strategies.
+///
+/// Game number `i` is played with the seed `seed + i`, which decides the
+/// starter, the layouts and every shot, and the contenders swap seats from
+/// one game to the next. The outcomes are thus the same for the same seed
+/// whatever the number of threads.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Simulation {
+    pub rules: Rules,
+    pub games: usize,
+    pub seed: usize,
+    pub threads: usize,
+}
+
+impl Simulation {
+    /// A simulation of games played by `rules`, which must be for two
+    /// players since contenders are matched up in pairs.
+    pub fn new(rules: Rules, games: usize, seed: usize) -> Option<Simulation> {
+        if rules.player_count() != 2 {
+            return None;
+        }
+        Some(Simulation {
+            rules: rules,
+            games: games,
+            seed: seed,
+            threads: DEFAULT_THREADS,
+        })
+    }
+    pub fn play(&self, first: &Contender, second: &Contender) -> MatchUp {
+        MatchUp::new(first.clone(), second.clone(), &self.outcomes(first, second))
+    }
+    /// Plays every game between `first` and `second`, in order.
+    pub fn outcomes(&self, first: &Contender, second: &Contender) -> Vec<GameOutcome> {
+        let threads = self.threads.max(1);
+        let workers: Vec<thread::JoinHandle<Vec<(usize, GameOutcome)>>> = (0..threads).map(|worker| {
+            let simulation = self.clone();
+            let contenders = vec![first.clone(), second.clone()];
+            thread::spawn(move || {
+                (worker..simulation.games).step_by(threads)
+                    .map(|number| (number, simulation.play_game(&contenders, number)))
+                    .collect()
+            })
+        }).collect();
+        let mut outcomes: Vec<(usize, GameOutcome)> = workers.into_iter()
+            .flat_map(|worker| worker.join().expect("a simulated game panicked"))
+            .collect();
+        outcomes.sort_by_key(|&(number, _)| number);
+        outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+    }
+    fn play_game(&self, contenders: &[Contender], number: usize) -> GameOutcome {
+        let seed = self.seed.wrapping_add(number);
+        let mut rng = StdRng::from_seed(&[seed]);
+        let seats = match number % 2 {
+            0 => vec![0, 1],
+            _ => vec![1, 0],
+        };
+        let mut game = Game::new_random_starter_with_rules(false, self.rules.clone(), &mut rng);
+        let gameplay = {
+            let players: Vec<Box<Player>> = seats.iter().enumerate().map(|(player, &contender)| {
+                let contender = &contenders[contender];
+                let layout = contender.placement.new_layout_with_mixed_fleet(&self.rules.spec, self.rules.fleets[player].clone(), &mut rng);
+                Box::new(AiPlayer::new_with_bombardment_strategy(layout, contender.bombardment.clone(), &mut rng)) as Box<Player>
+            }).collect();
+            Engine::new(&mut game, players).run(|_, _| { })
+        };
+        // The strategies always find a target, so the game only stops early
+        // if one of them is broken, which loses it the game to the other
+        // contender, `new` allowing two players only.
+        let winner = match gameplay {
+            GamePlay::Won(winner) => winner,
+            gameplay => 1 - gameplay.player(),
+        };
+        let shots = game.moves.iter().filter(|played| match played {
+            &&Move::Bombarded { player, ref result, .. } => player == winner && *result != BombardmentResult::Retry,
+            _ => false,
+        }).count();
+        GameOutcome {
+            seed: seed,
+            winner: seats[winner],
+            shots: shots,
+        }
+    }
+}
@@ -2,17 +2,19 @@ use super::*;
 
 #[test]
 fn read_coordletter_from_str() {
-    assert_eq!(Some(CoordLetter::A), CoordLetter::new("A"));
-    assert_eq!(Some(CoordLetter::B), CoordLetter::new("B"));
-    assert_eq!(Some(CoordLetter::C), CoordLetter::new("C"));
-    assert_eq!(Some(CoordLetter::D), CoordLetter::new("D"));
-    assert_eq!(Some(CoordLetter::E), CoordLetter::new("E"));
-    assert_eq!(Some(CoordLetter::F), CoordLetter::new("F"));
-    assert_eq!(Some(CoordLetter::G), CoordLetter::new("G"));
-    assert_eq!(Some(CoordLetter::H), CoordLetter::new("H"));
-    assert_eq!(Some(CoordLetter::I), CoordLetter::new("I"));
-    assert_eq!(Some(CoordLetter::J), CoordLetter::new("J"));
-    assert_eq!(None, CoordLetter::new("X"));
+    assert_eq!(Some(CoordLetter::from(0)), CoordLetter::new("A"));
+    assert_eq!(Some(CoordLetter::from(1)), CoordLetter::new("B"));
+    assert_eq!(Some(CoordLetter::from(2)), CoordLetter::new("C"));
+    assert_eq!(Some(CoordLetter::from(3)), CoordLetter::new("D"));
+    assert_eq!(Some(CoordLetter::from(4)), CoordLetter::new("E"));
+    assert_eq!(Some(CoordLetter::from(5)), CoordLetter::new("F"));
+    assert_eq!(Some(CoordLetter::from(6)), CoordLetter::new("G"));
+    assert_eq!(Some(CoordLetter::from(7)), CoordLetter::new("H"));
+    assert_eq!(Some(CoordLetter::from(8)), CoordLetter::new("I"));
+    assert_eq!(Some(CoordLetter::from(9)), CoordLetter::new("J"));
+    assert_eq!(Some(CoordLetter::from(25)), CoordLetter::new("Z"));
+    assert_eq!(None, CoordLetter::new("a"));
+    assert_eq!(None, CoordLetter::new("AB"));
 }
 #[test]
 fn convert_coordletter_to_str() {
@@ -20,7 +22,11 @@ fn convert_coordletter_to_str() {
 }
 #[test]
 fn read_coordnum_from_str() {
-    assert_eq!(Some(CoordNum::One), CoordNum::new("1"));
+    assert_eq!(Some(CoordNum::from(0)), CoordNum::new("1"));
+    assert_eq!(Some(CoordNum::from(25)), CoordNum::new("26"));
+    assert_eq!(None, CoordNum::new("0"));
+    assert_eq!(None, CoordNum::new("05"));
+    assert_eq!(None, CoordNum::new("27"));
 }
 #[test]
 fn convert_coordnum_to_str() {
@@ -28,21 +34,25 @@ fn convert_coordnum_to_str() {
 }
 #[test]
 fn move_coordletter() {
-    let a = CoordLetter::A;
+    let a = CoordLetter::new("A").unwrap();
     assert_eq!(None, a.new_moved_by(-1));
-    assert_eq!(Some(CoordLetter::B), a.new_moved_by(1));
-    let j = CoordLetter::J;
-    assert_eq!(None, j.new_moved_by(1));
-    assert_eq!(Some(CoordLetter::I), j.new_moved_by(-1));
+    assert_eq!(CoordLetter::new("B"), a.new_moved_by(1));
+    let j = CoordLetter::new("J").unwrap();
+    assert_eq!(CoordLetter::new("K"), j.new_moved_by(1));
+    assert_eq!(CoordLetter::new("I"), j.new_moved_by(-1));
+    let z = CoordLetter::new("Z").unwrap();
+    assert_eq!(None, z.new_moved_by(1));
 }
 #[test]
 fn move_coordnum() {
-    let one = CoordNum::One;
+    let one = CoordNum::new("1").unwrap();
     assert_eq!(None, one.new_moved_by(-1));
-    assert_eq!(Some(CoordNum::Two), one.new_moved_by(1));
-    let ten = CoordNum::Ten;
-    assert_eq!(None, ten.new_moved_by(1));
-    assert_eq!(Some(CoordNum::Nine), ten.new_moved_by(-1));
+    assert_eq!(CoordNum::new("2"), one.new_moved_by(1));
+    let ten = CoordNum::new("10").unwrap();
+    assert_eq!(CoordNum::new("11"), ten.new_moved_by(1));
+    assert_eq!(CoordNum::new("9"), ten.new_moved_by(-1));
+    let last = CoordNum::new("26").unwrap();
+    assert_eq!(None, last.new_moved_by(1));
 }
 #[test]
 fn convert_coordinate_to_str() {
@@ -56,7 +66,7 @@ fn coordinate_equality() {
 #[test]
 fn iterate_tiles_all_visible_north() {
     let p = Plane::new("E5", "N").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates = vec!["C6", "D6", "E6", "F6", "G6", "E7", "D8", "E8", "F8"];
     for expected in expected_coordinates {
         assert_eq!(expected, format!("{}", iter.next().unwrap().unwrap()));
@@ -66,7 +76,7 @@ fn iterate_tiles_all_visible_north() {
 #[test]
 fn iterate_tiles_all_invisible_north() {
     let p = Plane::new("J10", "N").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     for _ in 0..9 {
         assert_eq!(Some(None), iter.next());
     }
@@ -75,7 +85,7 @@ fn iterate_tiles_all_invisible_north() {
 #[test]
 fn iterate_tiles_lefthand_invisible_north() {
     let p = Plane::new("A1", "N").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         None, None, Coordinate::new("A2"), Coordinate::new("B2"), Coordinate::new("C2"),
         Coordinate::new("A3"),
@@ -89,7 +99,7 @@ fn iterate_tiles_lefthand_invisible_north() {
 #[test]
 fn iterate_tiles_righthand_invisible_north() {
     let p = Plane::new("J1", "N").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         Coordinate::new("H2"), Coordinate::new("I2"), Coordinate::new("J2"), None, None,
         Coordinate::new("J3"),
@@ -103,7 +113,7 @@ fn iterate_tiles_righthand_invisible_north() {
 #[test]
 fn iterate_tiles_all_visible_south() {
     let p = Plane::new("E5", "S").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates = vec!["G4", "F4", "E4", "D4", "C4", "E3", "F2", "E2", "D2"];
     for expected in expected_coordinates {
         assert_eq!(expected, format!("{}", iter.next().unwrap().unwrap()));
@@ -113,7 +123,7 @@ fn iterate_tiles_all_visible_south() {
 #[test]
 fn iterate_tiles_all_invisible_south() {
     let p = Plane::new("J10", "N").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     for _ in 0..9 {
         assert_eq!(Some(None), iter.next());
     }
@@ -122,7 +132,7 @@ fn iterate_tiles_all_invisible_south() {
 #[test]
 fn iterate_tiles_lefthand_invisible_south() {
     let p = Plane::new("A1", "N").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         None, None, Coordinate::new("A2"), Coordinate::new("B2"), Coordinate::new("C2"),
         Coordinate::new("A3"),
@@ -136,7 +146,7 @@ fn iterate_tiles_lefthand_invisible_south() {
 #[test]
 fn iterate_tiles_righthand_invisible_south() {
     let p = Plane::new("J1", "N").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         Coordinate::new("H2"), Coordinate::new("I2"), Coordinate::new("J2"), None, None,
         Coordinate::new("J3"),
@@ -150,7 +160,7 @@ fn iterate_tiles_righthand_invisible_south() {
 #[test]
 fn iterate_tiles_all_visible_east() {
     let p = Plane::new("E5", "E").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates = vec!["D3", "D4", "D5", "D6", "D7", "C5", "B4", "B5", "B6"];
     for expected in expected_coordinates {
         assert_eq!(expected, format!("{}", iter.next().unwrap().unwrap()));
@@ -160,7 +170,7 @@ fn iterate_tiles_all_visible_east() {
 #[test]
 fn iterate_tiles_all_invisible_east() {
     let p = Plane::new("A10", "E").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     for _ in 0..9 {
         assert_eq!(Some(None), iter.next());
     }
@@ -169,7 +179,7 @@ fn iterate_tiles_all_invisible_east() {
 #[test]
 fn iterate_tiles_lefthand_invisible_east() {
     let p = Plane::new("J1", "E").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         None, None, Coordinate::new("I1"), Coordinate::new("I2"), Coordinate::new("I3"),
         Coordinate::new("H1"),
@@ -183,7 +193,7 @@ fn iterate_tiles_lefthand_invisible_east() {
 #[test]
 fn iterate_tiles_righthand_invisible_east() {
     let p = Plane::new("J10", "E").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         Coordinate::new("I8"), Coordinate::new("I9"), Coordinate::new("I10"), None, None,
         Coordinate::new("H10"),
@@ -197,7 +207,7 @@ fn iterate_tiles_righthand_invisible_east() {
 #[test]
 fn iterate_tiles_all_visible_west() {
     let p = Plane::new("E5", "W").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates = vec!["F7", "F6", "F5", "F4", "F3", "G5", "H6", "H5", "H4"];
     for expected in expected_coordinates {
         assert_eq!(expected, format!("{}", iter.next().unwrap().unwrap()));
@@ -207,7 +217,7 @@ fn iterate_tiles_all_visible_west() {
 #[test]
 fn iterate_tiles_all_invisible_west() {
     let p = Plane::new("J10", "W").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     for _ in 0..9 {
         assert_eq!(Some(None), iter.next());
     }
@@ -236,7 +246,7 @@ fn bug_tiles_all_visible_a3_w() {
 #[test]
 fn iterate_tiles_lefthand_invisible_west() {
     let p = Plane::new("A10", "W").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         None, None, Coordinate::new("B10"), Coordinate::new("B9"), Coordinate::new("B8"),
         Coordinate::new("C10"),
@@ -250,7 +260,7 @@ fn iterate_tiles_lefthand_invisible_west() {
 #[test]
 fn iterate_tiles_righthand_invisible_west() {
     let p = Plane::new("A1", "W").unwrap();
-    let mut iter = p.coordinate_iterator();
+    let mut iter = p.coordinate_iterator_on(&BoardSpec::default());
     let expected_coordinates : Vec<Option<Coordinate>> = vec![
         Coordinate::new("B3"), Coordinate::new("B2"), Coordinate::new("B1"), None, None,
         Coordinate::new("C1"),
@@ -266,7 +276,7 @@ fn not_outside_of_map() {
     let heads = vec![("E5", "N"), ("E5", "S"), ("E5", "E"), ("E5", "W")];
     for plane_head in heads {
         let p = Plane::new(plane_head.0, plane_head.1).unwrap();
-        assert_eq!(false, p.is_outside_of_map());
+        assert_eq!(false, p.is_outside_of_map(&BoardSpec::default()));
     }
 }
 #[test]
@@ -281,7 +291,7 @@ fn outside_of_map() {
     ];
     for plane_head in heads {
         let p = Plane::new(plane_head.0, plane_head.1).unwrap();
-        assert_eq!(true, p.is_outside_of_map());
+        assert_eq!(true, p.is_outside_of_map(&BoardSpec::default()));
     }
 }
 #[test]
@@ -336,3 +346,869 @@ fn find_plane_at() {
         }
     }
 }
+#[test]
+fn players_take_turns_placing_then_bombarding() {
+    let mut game = Game::new_with_starter(2, 1, true);
+    let layouts = vec![("C2", "N"), ("H2", "N"), ("E6", "N")];
+    for &(head, orientation) in &layouts {
+        assert_eq!(GamePlay::PlaceNewPlane(1), game.gameplay);
+        game.add_new_plane_at(1, head, orientation).unwrap();
+        game.next_logical_state();
+        assert_eq!(GamePlay::PlaceNewPlane(0), game.gameplay);
+        game.add_new_plane_at(0, head, orientation).unwrap();
+        game.next_logical_state();
+    }
+    assert_eq!(GamePlay::Bombard(1), game.gameplay);
+    assert_eq!(Some(0), game.opponent_of(1));
+}
+#[test]
+fn killing_all_planes_wins_the_game() {
+    let mut game = Game::new_with_starter(2, 0, true);
+    let layouts = vec![("C2", "N"), ("H2", "N"), ("E6", "N")];
+    for &(head, orientation) in &layouts {
+        game.add_new_plane_at(0, head, orientation).unwrap();
+        game.next_logical_state();
+        game.add_new_plane_at(1, head, orientation).unwrap();
+        game.next_logical_state();
+    }
+    assert_eq!(GamePlay::Bombard(0), game.gameplay);
+    let misses = vec!["A10", "B10"];
+    for (i, &(head, _)) in layouts.iter().enumerate() {
+        assert_eq!(BombardmentResult::Kill("classic".to_string()), game.hit_at(0, head));
+        game.next_logical_state();
+        if i < misses.len() {
+            assert_eq!(GamePlay::Bombard(1), game.gameplay);
+            assert_eq!(BombardmentResult::Miss, game.hit_at(1, misses[i]));
+            assert_eq!(BombardmentResult::Retry, game.hit_at(1, misses[i]));
+            game.next_logical_state();
+        }
+    }
+    assert_eq!(GamePlay::Won(0), game.gameplay);
+    assert_eq!(3, game.players[0].scrapbook.killed_planes().len());
+}
+#[test]
+fn ai_player_follows_its_layout() {
+    let layout = Board::new_random();
+    let mut ai = AiPlayer::new(layout.clone());
+    let mut game = Game::new_with_starter(2, 1, false);
+    for _ in 0..3 {
+        let (head, orientation) = ai.next_plane(&game, 1).unwrap();
+        game.add_new_plane(1, head, orientation).unwrap();
+    }
+    assert!(ai.next_plane(&game, 1).is_none());
+    assert_eq!(format!("{}", layout), format!("{}", game.players[1].board));
+}
+#[test]
+fn engine_drives_two_ai_players_to_the_end() {
+    let mut game = Game::new_random_starter(2, true);
+    let mut bombardments = 0;
+    let gameplay = {
+        let players: Vec<Box<Player>> = vec![Box::new(AiPlayer::new_random()), Box::new(AiPlayer::new_random())];
+        let mut engine = Engine::new(&mut game, players);
+        engine.run(|_, event| {
+            if let &Event::Bombarded { .. } = event {
+                bombardments += 1;
+            }
+        })
+    };
+    assert!(gameplay.is_over());
+    assert!(bombardments >= 6);
+    assert!(game.players[gameplay.player()].scrapbook.killed_planes().len() == 3);
+}
+#[test]
+fn engine_waits_for_queued_player() {
+    let mut game = Game::new_with_starter(2, 0, false);
+    let mut human = QueuedPlayer::new();
+    human.push_plane(Coordinate::new("E5").unwrap(), Orientation::new("N").unwrap());
+    let players: Vec<Box<Player>> = vec![Box::new(human), Box::new(AiPlayer::new_random())];
+    let mut engine = Engine::new(&mut game, players);
+    let mut events = Vec::new();
+    let gameplay = engine.run(|_, event| events.push(event.clone()));
+    assert_eq!(GamePlay::PlaceNewPlane(0), gameplay);
+    assert_eq!(Some(&Event::Waiting(0)), events.last());
+    assert_eq!(1, engine.game().players[1].board.planes().len());
+}
+#[test]
+fn placement_errors() {
+    let mut board = Board::new();
+    assert_eq!(Err(PlacementError::InvalidCoordinate("K5".to_string())), board.add_new_plane_at("K5", "N"));
+    assert_eq!(Err(PlacementError::InvalidCoordinate("".to_string())), board.add_new_plane_at("", "N"));
+    assert_eq!(Err(PlacementError::InvalidOrientation("X".to_string())), board.add_new_plane_at("E5", "X"));
+    assert_eq!(Err(PlacementError::OutOfMap), board.add_new_plane_at("A1", "N"));
+    assert_eq!(Ok(1), board.add_new_plane_at("C1", "N"));
+    assert_eq!(Ok(2), board.add_new_plane_at("H1", "N"));
+    assert_eq!(Err(PlacementError::Overlap { with: vec![1, 2] }), board.add_new_plane_at("E1", "N"));
+    assert_eq!(Some(&PlacementError::Overlap { with: vec![1, 2] }), board.previous_error());
+    assert_eq!(Ok(3), board.add_new_plane_at("E6", "N"));
+    assert_eq!(None, board.previous_error());
+    assert_eq!(Err(PlacementError::TooManyPlanes), board.add_new_plane_at("E6", "S"));
+}
+#[test]
+fn board_spec_sets_the_size_of_the_map() {
+    assert_eq!(Some(BoardSpec { width: 15, height: 12 }), BoardSpec::new_from_str("15x12"));
+    assert_eq!(None, BoardSpec::new_from_str("4x4"));
+    assert_eq!(None, BoardSpec::new_from_str("30x10"));
+    assert_eq!(None, BoardSpec::new_from_str("10"));
+
+    let spec = BoardSpec::new(15, 15).unwrap();
+    let mut board = Board::new_with_spec(spec);
+    assert_eq!(225, board.empty_indices().len());
+    assert_eq!(Ok(1), board.add_new_plane_at("M12", "N"));
+    assert_eq!(Err(PlacementError::OutOfMap), board.add_new_plane_at("O14", "N"));
+    assert_eq!(Err(PlacementError::InvalidCoordinate("P5".to_string())), board.add_new_plane_at("P5", "N"));
+
+    let mut small = Board::new_with_spec(BoardSpec::new(8, 8).unwrap());
+    assert_eq!(Err(PlacementError::InvalidCoordinate("I5".to_string())), small.add_new_plane_at("I5", "S"));
+    assert_eq!(Err(PlacementError::OutOfMap), small.add_new_plane_at("E7", "N"));
+}
+#[test]
+fn game_and_board_agree_on_heads_off_the_map() {
+    let spec = BoardSpec::new(8, 8).unwrap();
+    let mut game = Game::new_with_spec(2, 0, false, spec);
+    let mut board = Board::new_with_spec(spec);
+    assert_eq!(board.add_new_plane_at("I5", "S"), game.add_new_plane_at(0, "I5", "S"));
+    assert_eq!(Err(PlacementError::InvalidCoordinate("I5".to_string())), game.add_new_plane_at(0, "I5", "S"));
+    assert_eq!(board.add_new_plane_at("E7", "N"), game.add_new_plane_at(0, "E7", "N"));
+}
+#[test]
+fn ais_play_a_whole_game_on_a_small_map() {
+    use rand::{SeedableRng, StdRng};
+    let spec = BoardSpec::new(8, 8).unwrap();
+    let mut rng = StdRng::from_seed(&[8]);
+    let mut game = Game::new_random_starter_with_spec(2, true, spec, &mut rng);
+    let players: Vec<Box<Player>> = vec![
+        Box::new(AiPlayer::new_random_with_spec(AiLevel::Intermediate, &spec, &mut rng)),
+        Box::new(AiPlayer::new_random_with_spec(AiLevel::Advanced, &spec, &mut rng)),
+    ];
+    let mut targets = Vec::new();
+    let winner = Engine::new(&mut game, players).run(|_, event| {
+        if let &Event::Bombarded { target, .. } = event {
+            targets.push(target);
+        }
+    });
+    match winner {
+        GamePlay::Won(_) => { },
+        other => panic!("game did not end: {}", other),
+    }
+    assert!(targets.iter().all(|target| spec.contains(target)));
+    assert_eq!(spec, *Replay::new(&game).game_at(0).spec());
+}
+#[test]
+fn rules_read_the_fleets_from_str() {
+    let spec = BoardSpec::default();
+    assert_eq!(Some(vec![3, 3]), Rules::new_from_fleet_str(spec, PlaneShape::default(), "3").map(|rules| rules.fleets.iter().map(Fleet::len).collect::<Vec<usize>>()));
+    assert_eq!(Some(vec![3, 2]), Rules::new_from_fleet_str(spec, PlaneShape::default(), "3:2").map(|rules| rules.fleets.iter().map(Fleet::len).collect::<Vec<usize>>()));
+    assert_eq!(None, Rules::new_from_fleet_str(spec, PlaneShape::default(), "0"));
+    assert_eq!(None, Rules::new_from_fleet_str(spec, PlaneShape::default(), "6"));
+    assert_eq!(None, Rules::new_from_fleet_str(spec, PlaneShape::default(), "3:2:1"));
+    assert_eq!(None, Rules::new_from_fleet_str(spec, PlaneShape::default(), "many"));
+    assert_eq!(11, Rules::max_fleet_size(&BoardSpec::new(15, 15).unwrap(), &PlaneShape::classic()));
+}
+#[test]
+fn rules_only_allow_fleets_which_can_be_laid_out() {
+    let small = BoardSpec::new(8, 8).unwrap();
+    assert!(!Rules::new_with_shape(small, vec![2, 2], PlaneShape::bomber()).unwrap().can_be_laid_out());
+    assert!(Rules::new_with_shape(small, vec![1, 1], PlaneShape::bomber()).unwrap().can_be_laid_out());
+    assert!(Rules::default().can_be_laid_out());
+    assert!(Fleet::default().fits_side_by_side(&BoardSpec::default()));
+    assert!(!Fleet::new_uniform(2, PlaneShape::bomber()).fits_side_by_side(&small));
+    let line = PlaneShape::new("line", (1..12).map(|y| (0, y)).collect()).unwrap();
+    assert_eq!(None, Rules::new_with_shape(BoardSpec::default(), vec![1, 1], line.clone()));
+    assert!(Rules::new_with_shape(BoardSpec::new(12, 12).unwrap(), vec![1, 1], line).is_some());
+    let layout = Board::new_first_fit_with_mixed_fleet(&BoardSpec::default(), Fleet::default()).unwrap();
+    assert!(layout.is_initialized());
+    assert!(Board::new_first_fit_with_mixed_fleet(&small, Fleet::new_uniform(2, PlaneShape::bomber())).is_none());
+}
+#[test]
+fn fleet_size_limits_the_planes_of_a_board() {
+    let rules = Rules::new(BoardSpec::default(), vec![1, 4]).unwrap();
+    let mut game = Game::new_with_rules(0, false, rules.clone());
+    assert_eq!(rules, game.rules());
+    assert_eq!(4, game.players[0].scrapbook.fleet_size());
+    assert_eq!(Ok(1), game.add_new_plane_at(0, "E5", "N"));
+    assert_eq!(Err(PlacementError::TooManyPlanes), game.add_new_plane_at(0, "B2", "N"));
+    assert!(game.players[0].board.is_initialized());
+    assert!(!game.players[1].board.is_initialized());
+}
+#[test]
+fn ais_play_a_whole_game_with_unequal_fleets() {
+    use rand::{SeedableRng, StdRng};
+    let rules = Rules::new(BoardSpec::default(), vec![4, 2]).unwrap();
+    let mut rng = StdRng::from_seed(&[17]);
+    let mut game = Game::new_random_starter_with_rules(true, rules.clone(), &mut rng);
+    let players: Vec<Box<Player>> = vec![
+        Box::new(AiPlayer::new_random_with_rules(AiLevel::Advanced, &rules, 0, &mut rng)),
+        Box::new(AiPlayer::new_random_with_rules(AiLevel::Advanced, &rules, 1, &mut rng)),
+    ];
+    let mut kills = vec![0, 0];
+    let winner = Engine::new(&mut game, players).run(|_, event| {
+        if let &Event::Bombarded { player, result: BombardmentResult::Kill(_), .. } = event {
+            kills[player] += 1;
+        }
+    });
+    match winner {
+        GamePlay::Won(winner) => assert_eq!(rules.opponent_fleet(winner).len(), kills[winner]),
+        other => panic!("game did not end: {}", other),
+    }
+    assert!(game.players.iter().all(|slot| slot.board.is_initialized()));
+    assert_eq!(4, game.players[0].board.planes().len() + game.players[0].board.killed_planes().len());
+    assert_eq!(rules, Replay::new(&game).game_at(0).rules());
+}
+#[test]
+fn plane_shapes_rotate_around_the_head() {
+    let classic = PlaneShape::new_from_drawing("classic", "  ^\nooooo\n  o\n ooo\n").unwrap();
+    assert_eq!(PlaneShape::classic(), classic);
+    assert_eq!(None, PlaneShape::new_from_drawing("headless", "ooo\n o"));
+    assert_eq!(None, PlaneShape::new_from_drawing("bodiless", " ^ "));
+    assert_eq!(None, PlaneShape::new_from_drawing("two-headed", "^^\noo"));
+    assert_eq!(None, PlaneShape::new("overlapping", vec![(0, 1), (0, 1)]));
+    assert_eq!(Some(PlaneShape::bomber()), PlaneShape::new_named("bomber"));
+    assert_eq!(15, PlaneShape::bomber().tile_count());
+
+    let head = Coordinate::new("E5").unwrap();
+    let expected = vec![("N", vec!["D6", "E6", "F6", "E7"]), ("S", vec!["F4", "E4", "D4", "E3"]),
+                        ("E", vec!["D4", "D5", "D6", "C5"]), ("W", vec!["F6", "F5", "F4", "G5"])];
+    for (orientation, tiles) in expected {
+        let plane = Plane::new_with_shape(head, Orientation::new(orientation).unwrap(), PlaneShape::fighter(), 1);
+        let actual: Vec<String> = plane.coordinate_iterator().filter_map(|t| t).map(|t| t.to_string()).collect();
+        assert_eq!(tiles, actual, "fighter flying {}", orientation);
+    }
+}
+#[test]
+fn plane_shapes_fit_on_the_largest_map() {
+    assert_eq!((7, 5), PlaneShape::bomber().extent());
+    assert!(PlaneShape::bomber().fits_on(&BoardSpec::new(8, 8).unwrap()));
+    let line = PlaneShape::new("line", (1..12).map(|y| (0, y)).collect()).unwrap();
+    assert!(!line.fits_on(&BoardSpec::default()));
+    assert!(line.fits_on(&BoardSpec::new(10, 12).unwrap()));
+    assert!(line.fits_on(&BoardSpec::new(12, 10).unwrap()));
+    assert_eq!(None, PlaneShape::new("long", (1..30).map(|y| (0, y)).collect()));
+}
+#[cfg(feature = "serialize")]
+#[test]
+fn plane_shapes_are_checked_when_read_from_json() {
+    let fighter: PlaneShape = serde_json::from_value(json!({"name": "fighter", "body": [[-1, 1], [0, 1], [1, 1], [0, 2]]})).unwrap();
+    assert_eq!(PlaneShape::fighter(), fighter);
+    assert!(serde_json::from_value::<PlaneShape>(json!({"name": "headless", "body": [[0, 0], [0, 1]]})).is_err());
+    assert!(serde_json::from_value::<PlaneShape>(json!({"name": "bodiless", "body": []})).is_err());
+    assert!(serde_json::from_value::<PlaneShape>(json!({"name": "long", "body": [[0, 1], [0, 40]]})).is_err());
+    assert!(serde_json::from_value::<PlaneShape>(json!({"name": "far", "body": [[2147483647, 0], [-1, 0]]})).is_err());
+    assert!(serde_json::from_value::<PlaneShape>(json!({"name": "far", "body": [[-2147483648, 0]]})).is_err());
+}
+#[test]
+fn boards_place_planes_of_their_shape() {
+    let mut board = Board::new_with_shape(BoardSpec::default(), 2, PlaneShape::fighter());
+    assert_eq!(Ok(1), board.add_new_plane_at("B1", "N"));
+    assert_eq!(Ok(2), board.add_new_plane_at("B5", "N"));
+    assert_eq!(&PlaneShape::fighter(), board.planes()[0].shape());
+    assert_eq!(90, board.empty_indices().len());
+    assert_eq!(BombardmentResult::Miss, board.hit_at(Coordinate::new("B4").unwrap()));
+    assert_eq!(BombardmentResult::Hit, board.hit_at(Coordinate::new("B3").unwrap()));
+
+    let mut bombers = Board::new_with_shape(BoardSpec::default(), 2, PlaneShape::bomber());
+    assert_eq!(Err(PlacementError::OutOfMap), bombers.add_new_plane_at("B1", "N"));
+    assert_eq!(Ok(1), bombers.add_new_plane_at("D1", "N"));
+    assert_eq!(Err(PlacementError::Overlap { with: vec![1] }), bombers.add_new_plane_at("E5", "S"));
+
+    let heatmap = Heatmap::new(&Board::new_with_shape(BoardSpec::default(), 2, PlaneShape::fighter()));
+    let bodies: f64 = heatmap.bodies().iter().map(|row| row.iter().sum::<f64>()).sum();
+    assert!((bodies - 8.0).abs() < 1e-9);
+}
+#[test]
+fn ais_play_a_whole_game_with_bombers() {
+    use rand::{SeedableRng, StdRng};
+    let rules = Rules::new_with_shape(BoardSpec::new(12, 12).unwrap(), vec![2, 2], PlaneShape::bomber()).unwrap();
+    assert_eq!(None, Rules::new_with_shape(BoardSpec::new(8, 8).unwrap(), vec![3, 3], PlaneShape::bomber()));
+    let mut rng = StdRng::from_seed(&[18]);
+    let mut game = Game::new_random_starter_with_rules(true, rules.clone(), &mut rng);
+    let players: Vec<Box<Player>> = vec![
+        Box::new(AiPlayer::new_random_with_rules(AiLevel::Intermediate, &rules, 0, &mut rng)),
+        Box::new(AiPlayer::new_random_with_rules(AiLevel::Advanced, &rules, 1, &mut rng)),
+    ];
+    match Engine::new(&mut game, players).run(|_, _| { }) {
+        GamePlay::Won(_) => { },
+        other => panic!("game did not end: {}", other),
+    }
+    assert!(game.players.iter().all(|slot| slot.board.planes().iter().chain(slot.board.killed_planes()).all(|plane| *plane.shape() == PlaneShape::bomber())));
+    assert_eq!(rules, Replay::new(&game).game_at(0).rules());
+}
+#[test]
+fn read_mixed_fleets_from_str() {
+    let fleet = Fleet::new_from_str("1 bomber, 2 fighters").unwrap();
+    assert_eq!(vec![PlaneShape::bomber(), PlaneShape::fighter(), PlaneShape::fighter()], *fleet.shapes());
+    assert_eq!("1 bomber, 2 fighters", fleet.to_string());
+    assert_eq!(Some(Fleet::new_uniform(1, PlaneShape::fighter())), Fleet::new_from_str("fighter"));
+    assert_eq!(None, Fleet::new_from_str("2 zeppelins"));
+    assert_eq!(None, Fleet::new_from_str("0 fighters"));
+
+    let spec = BoardSpec::default();
+    let rules = Rules::new_from_fleet_str(spec, PlaneShape::default(), "1 bomber, 2 fighters:3").unwrap();
+    assert_eq!(fleet, rules.fleets[0]);
+    assert_eq!(Fleet::default(), rules.fleets[1]);
+    assert_eq!(None, Rules::new_from_fleet_str(spec, PlaneShape::default(), "4 bombers"));
+}
+#[test]
+fn mixed_fleets_are_placed_in_order_and_killed_by_type() {
+    let rules = Rules::new_from_fleet_str(BoardSpec::default(), PlaneShape::default(), "1 bomber, 1 fighter").unwrap();
+    let mut game = Game::new_with_rules(0, true, rules);
+    assert_eq!(Some(&PlaneShape::bomber()), game.players[1].board.next_shape());
+    game.add_new_plane_at(1, "D1", "N").unwrap();
+    assert_eq!(Some(&PlaneShape::fighter()), game.players[1].board.next_shape());
+    game.add_new_plane_at(1, "H7", "N").unwrap();
+    assert_eq!(None, game.players[1].board.next_shape());
+    assert_eq!(Err(PlacementError::TooManyPlanes), game.players[1].board.add_new_plane_at("A9", "E"));
+    assert_eq!(&PlaneShape::bomber(), game.players[1].board.planes()[0].shape());
+
+    game.add_new_plane_at(0, "D1", "N").unwrap();
+    game.add_new_plane_at(0, "H7", "N").unwrap();
+    game.gameplay = GamePlay::Bombard(0);
+    assert_eq!(BombardmentResult::Kill("fighter".to_string()), game.hit_at(0, "H7"));
+    assert_eq!(BombardmentResult::Kill("bomber".to_string()), game.players[1].board.hit_at(Coordinate::new("D1").unwrap()));
+    assert_eq!(&PlaneShape::fighter(), game.players[0].scrapbook.killed_planes()[0].shape());
+
+    let heatmap = Heatmap::new(&game.players[0].scrapbook);
+    assert!(heatmap.layouts() > 0);
+    let heads: f64 = heatmap.heads().iter().map(|row| row.iter().sum::<f64>()).sum();
+    let bodies: f64 = heatmap.bodies().iter().map(|row| row.iter().sum::<f64>()).sum();
+    assert!((heads - 2.0).abs() < 1e-9);
+    assert!((bodies - 18.0).abs() < 1e-9);
+}
+#[test]
+fn ais_play_a_whole_game_with_mixed_fleets() {
+    use rand::{SeedableRng, StdRng};
+    let rules = Rules::new_from_fleet_str(BoardSpec::default(), PlaneShape::default(), "1 bomber, 2 fighters").unwrap();
+    let mut rng = StdRng::from_seed(&[19]);
+    let mut game = Game::new_random_starter_with_rules(false, rules.clone(), &mut rng);
+    let players: Vec<Box<Player>> = vec![
+        Box::new(AiPlayer::new_random_with_rules(AiLevel::Intermediate, &rules, 0, &mut rng)),
+        Box::new(AiPlayer::new_random_with_rules(AiLevel::Advanced, &rules, 1, &mut rng)),
+    ];
+    let mut killed = vec![Vec::new(), Vec::new()];
+    let winner = Engine::new(&mut game, players).run(|_, event| {
+        if let &Event::Bombarded { player, result: BombardmentResult::Kill(ref shape), .. } = event {
+            killed[player].push(shape.clone());
+        }
+    });
+    match winner {
+        GamePlay::Won(winner) => {
+            killed[winner].sort();
+            assert_eq!(vec!["bomber", "fighter", "fighter"], killed[winner]);
+        },
+        other => panic!("game did not end: {}", other),
+    }
+    assert_eq!(rules, game.rules());
+}
+#[test]
+fn board_reads_back_its_layout_str() {
+    let board = Board::from_layout_str("C2N H2N E6N").unwrap();
+    assert_eq!("C2N H2N E6N ", board.to_string());
+    assert!(board.is_initialized());
+    assert_eq!(None, board.previous_error());
+    assert_eq!(board.to_string(), Board::from_layout_str(&board.to_string()).unwrap().to_string());
+
+    let random = Board::new_random();
+    assert_eq!(random.to_string(), Board::from_layout_str(&random.to_string()).unwrap().to_string());
+
+    let fleet = Fleet::new_from_str("1 bomber, 1 fighter").unwrap();
+    let mixed = Board::from_layout_str_with_fleet(BoardSpec::new(12, 12).unwrap(), fleet, "E5E B10N").unwrap();
+    assert_eq!(&PlaneShape::bomber(), mixed.planes()[0].shape());
+    assert_eq!(&PlaneShape::fighter(), mixed.planes()[1].shape());
+}
+#[test]
+fn board_reports_every_error_of_a_layout_str() {
+    let errors = Board::from_layout_str("C2N A1N C3N K1N E5X H2N H4S E6N J9E").err().unwrap();
+    assert_eq!(vec![
+        LayoutError::Plane { position: 2, plane: "A1N".to_string(), error: PlacementError::OutOfMap },
+        LayoutError::Plane { position: 3, plane: "C3N".to_string(), error: PlacementError::Overlap { with: vec![1] } },
+        LayoutError::Plane { position: 4, plane: "K1N".to_string(), error: PlacementError::InvalidCoordinate("K1".to_string()) },
+        LayoutError::Plane { position: 5, plane: "E5X".to_string(), error: PlacementError::InvalidOrientation("X".to_string()) },
+        LayoutError::Plane { position: 7, plane: "H4S".to_string(), error: PlacementError::Overlap { with: vec![6] } },
+        LayoutError::Plane { position: 9, plane: "J9E".to_string(), error: PlacementError::TooManyPlanes },
+    ], errors);
+    assert_eq!("plane 3 (C3N): plane would overlap with: 1", errors[1].to_string());
+
+    let errors = Board::from_layout_str("E5N").err().unwrap();
+    assert_eq!(vec![LayoutError::MissingPlanes(2)], errors);
+    assert_eq!("2 planes are missing", errors[0].to_string());
+}
+#[test]
+fn hunt_and_target_aims_at_the_only_possible_head() {
+    let plane = Plane::new("E5", "N").unwrap();
+    let mut scrapbook = Board::new();
+    for tile in 0..100 {
+        let coord = Coordinate::new_from_usize(tile, scrapbook.spec());
+        if coord == *plane.head() {
+            continue;
+        }
+        scrapbook.empty_indices_mut().remove(&tile);
+        if plane.has_tile(&coord) {
+            scrapbook.hits_mut().push(coord);
+        } else {
+            scrapbook.misses_mut().push(coord);
+        }
+    }
+    assert_eq!(Some(*plane.head()), BombardmentStrategy::HuntAndTarget.next_target(&scrapbook, &Board::new()));
+}
+#[test]
+fn hunt_and_target_hunts_again_after_a_kill() {
+    let mut game = Game::new_with_starter(2, 0, false);
+    for &(head, orientation) in &[("C2", "N"), ("H2", "N"), ("E6", "N")] {
+        game.add_new_plane_at(0, head, orientation).unwrap();
+        game.add_new_plane_at(1, head, orientation).unwrap();
+    }
+    game.gameplay = GamePlay::Bombard(0);
+    assert_eq!(BombardmentResult::Hit, game.hit_at(0, "E7"));
+    let target = BombardmentStrategy::HuntAndTarget.next_target(&game.players[0].scrapbook, &game.players[1].board).unwrap();
+    let hit = Coordinate::new("E7").unwrap();
+    let candidates = bombardment_strategy::candidate_placements(&game.players[0].scrapbook);
+    assert!(candidates.iter().any(|plane| *plane.head() == target && plane.has_tile(&hit)), "{} cannot be a head near E7", target);
+    assert_eq!(BombardmentResult::Kill("classic".to_string()), game.hit_at(0, "E6"));
+    assert!(bombardment_strategy::open_hits(&game.players[0].scrapbook).is_empty());
+}
+#[test]
+fn read_ai_level_from_str() {
+    for level in vec![AiLevel::Beginner, AiLevel::Intermediate, AiLevel::Advanced, AiLevel::Hack, AiLevel::Auto, AiLevel::Adaptive] {
+        assert_eq!(Some(level), AiLevel::new(format!("{}", level).as_str()));
+    }
+    assert_eq!(None, AiLevel::new("godlike"));
+}
+#[test]
+fn hack_level_needs_one_shot_per_plane() {
+    let mut game = Game::new_with_starter(2, 0, false);
+    let mut bombardments = vec![0, 0];
+    let winner = {
+        let players: Vec<Box<Player>> = vec![
+            Box::new(AiPlayer::new_random_with_level(AiLevel::Hack)),
+            Box::new(AiPlayer::new_random_with_level(AiLevel::Beginner)),
+        ];
+        let mut engine = Engine::new(&mut game, players);
+        engine.run(|_, event| {
+            if let &Event::Bombarded { player, .. } = event {
+                bombardments[player] += 1;
+            }
+        })
+    };
+    assert_eq!(GamePlay::Won(0), winner);
+    assert_eq!(3, bombardments[0]);
+}
+#[test]
+fn heatmap_of_an_empty_scrapbook_adds_up_to_the_fleet() {
+    let heatmap = Heatmap::new(&Board::new());
+    assert!(heatmap.layouts() > 0);
+    let heads: f64 = heatmap.heads().iter().map(|row| row.iter().sum::<f64>()).sum();
+    let bodies: f64 = heatmap.bodies().iter().map(|row| row.iter().sum::<f64>()).sum();
+    assert!((heads - 3.0).abs() < 1e-9);
+    assert!((bodies - 27.0).abs() < 1e-9);
+    assert_eq!(0.0, heatmap.head_probability(&Coordinate::new("A1").unwrap()));
+}
+#[test]
+fn heatmap_pins_down_a_wounded_plane() {
+    let plane = Plane::new("E5", "N").unwrap();
+    let mut scrapbook = Board::new();
+    for tile in 0..100 {
+        let coord = Coordinate::new_from_usize(tile, scrapbook.spec());
+        if coord.as_tuple().1 >= 4 && coord.as_tuple().1 <= 7 && !plane.has_tile(&coord) && coord != *plane.head() {
+            scrapbook.empty_indices_mut().remove(&tile);
+            scrapbook.misses_mut().push(coord);
+        }
+    }
+    let hit = Coordinate::new("E6").unwrap();
+    scrapbook.empty_indices_mut().remove(&hit.as_usize(&BoardSpec::default()));
+    scrapbook.hits_mut().push(hit);
+    let heatmap = Heatmap::new(&scrapbook);
+    assert!(heatmap.layouts() > 0);
+    assert_eq!(1.0, heatmap.body_probability(&hit));
+    let e5 = heatmap.head_probability(&Coordinate::new("E5").unwrap());
+    assert!(heatmap.heads().iter().all(|row| row.iter().all(|p| *p <= e5)));
+    assert_eq!(0.0, heatmap.plane_probability(&Coordinate::new("A5").unwrap()));
+}
+#[test]
+fn same_seed_replays_the_same_game() {
+    use rand::{SeedableRng, StdRng};
+    fn play(seed: usize) -> Vec<Event> {
+        let mut rng = StdRng::from_seed(&[seed]);
+        let mut game = Game::new_random_starter_with_rng(2, false, &mut rng);
+        let players: Vec<Box<Player>> = vec![
+            Box::new(AiPlayer::new_random_with_rng(AiLevel::Beginner, &mut rng)),
+            Box::new(AiPlayer::new_random_with_rng(AiLevel::Intermediate, &mut rng)),
+        ];
+        let mut events = Vec::new();
+        Engine::new(&mut game, players).run(|_, event| events.push(event.clone()));
+        events
+    }
+    let replayed = play(42);
+    match replayed.last() {
+        Some(&Event::GameOver { .. }) => { },
+        other => panic!("game did not end: {:?}", other),
+    }
+    assert_eq!(replayed, play(42));
+    assert!(replayed != play(43));
+}
+#[cfg(feature = "serialize")]
+#[test]
+fn game_survives_a_json_round_trip() {
+    let mut game = Game::new_with_starter(2, 0, true);
+    game.add_new_plane_at(0, "E5", "N").unwrap();
+    game.players[0].scrapbook.misses_mut().push(Coordinate::new("A10").unwrap());
+
+    let json = serde_json::to_value(&game).unwrap();
+    assert_eq!(json!({"head": "E5", "orientation": "N", "id": 1}), json["players"][0]["board"]["planes"][0]);
+    assert_eq!(json!("A10"), json["players"][0]["scrapbook"]["misses"][0]);
+    assert_eq!(json!({"PlaceNewPlane": 0}), json["gameplay"]);
+    assert_eq!("{\"Kill\":\"classic\"}", serde_json::to_string(&BombardmentResult::Kill("classic".to_string())).unwrap());
+
+    let restored: Game = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(json, serde_json::to_value(&restored).unwrap());
+    assert!(serde_json::from_str::<Coordinate>("\"A0\"").is_err());
+    assert!(serde_json::from_str::<Orientation>("\"X\"").is_err());
+}
+#[cfg(feature = "serialize")]
+#[test]
+fn saved_game_resumes_where_it_stopped() {
+    let mut game = Game::new_with_starter(2, 1, false);
+    let ai = AiPlayer::new_random_with_level(AiLevel::Advanced);
+    {
+        let mut human = QueuedPlayer::new();
+        human.push_plane(Coordinate::new("E5").unwrap(), Orientation::new("N").unwrap());
+        let players: Vec<Box<Player>> = vec![Box::new(human), Box::new(ai.clone())];
+        Engine::new(&mut game, players).run(|_, _| { });
+    }
+    let saved = SavedGame::from_json(&SavedGame::new(&game, &ai).to_json()).unwrap();
+    assert_eq!(AiLevel::Advanced, saved.ai_level);
+    assert_eq!(format!("{}", ai.layout()), format!("{}", saved.ai_player().layout()));
+    assert_eq!(GamePlay::PlaceNewPlane(0), saved.game.gameplay);
+    assert_eq!(1, saved.game.players[0].board.planes().len());
+    assert_eq!(2, saved.game.players[1].board.planes().len());
+
+    let future = SavedGame::new(&game, &ai).to_json().replacen("\"version\": 1", "\"version\": 2", 1);
+    match SavedGame::from_json(&future) {
+        Err(SaveError::UnsupportedVersion(2)) => { },
+        _ => panic!("a newer save format must be rejected"),
+    }
+}
+#[cfg(feature = "serialize")]
+#[test]
+fn public_moves_hide_where_planes_are_placed() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[3]);
+    let mut game = Game::new_with_starter(2, 0, false);
+    let ai = AiPlayer::new_random_with_rng(AiLevel::Advanced, &mut rng);
+    // As answering a plane placed through the API, the AI placing its planes
+    // in turn.
+    let mut human = QueuedPlayer::new();
+    human.push_plane(Coordinate::new("E5").unwrap(), Orientation::new("N").unwrap());
+    let players: Vec<Box<Player>> = vec![Box::new(human), Box::new(ai.clone())];
+    Engine::new(&mut game, players).run(|_, _| { });
+    let public: Vec<serde_json::Value> = game.moves.iter().map(Move::to_public_json).collect();
+    assert!(public.contains(&json!({"Placed": {"player": 1}})));
+    assert!(public.contains(&json!({"StateChanged": {"PlaceNewPlane": 0}})));
+    let text = serde_json::to_string(&public).unwrap();
+    assert!(!text.contains("head") && !text.contains("orientation"));
+    for plane in ai.layout().planes() {
+        assert!(!text.contains(&format!("\"{}\"", plane.head())));
+    }
+}
+#[cfg(feature = "serialize")]
+#[test]
+fn saved_games_off_the_map_are_rejected() {
+    let mut game = Game::new_with_starter(2, 0, false);
+    game.add_new_plane_at(0, "E5", "N").unwrap();
+    let ai = AiPlayer::new_random_with_level(AiLevel::Advanced);
+    let json = serde_json::to_value(SavedGame::new(&game, &ai)).unwrap();
+    let broken = |path: &[&str], value: serde_json::Value| {
+        let mut json = json.clone();
+        {
+            let mut field = &mut json;
+            for key in path {
+                field = match key.parse::<usize>() {
+                    Ok(index) => &mut field[index],
+                    Err(_) => &mut field[*key],
+                };
+            }
+            *field = value;
+        }
+        SavedGame::from_json(&json.to_string())
+    };
+    assert!(broken(&["version"], json!(1)).is_ok());
+    let rejected = vec![
+        broken(&["game", "players", "0", "board", "spec"], json!({"width": 0, "height": 10})),
+        broken(&["ai_layout", "spec"], json!({"width": 30, "height": 30})),
+        broken(&["game", "players"], json!([])),
+        broken(&["game", "gameplay"], json!({"Bombard": 5})),
+        broken(&["game", "players", "1", "scrapbook", "empty_indices"], json!([500])),
+        broken(&["game", "players", "0", "board", "misses"], json!(["Z26"])),
+        broken(&["game", "players", "0", "board", "planes", "0", "head"], json!("A1")),
+        broken(&["game", "players", "1", "scrapbook", "spec"], json!({"width": 12, "height": 12})),
+    ];
+    for result in rejected {
+        match result {
+            Err(SaveError::Format(_)) => { },
+            _ => panic!("a save off the map must be rejected"),
+        }
+    }
+}
+#[test]
+fn replay_rebuilds_the_game_at_every_ply() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[7]);
+    let mut game = Game::new_random_starter_with_rng(2, true, &mut rng);
+    {
+        let players: Vec<Box<Player>> = vec![
+            Box::new(AiPlayer::new_random_with_rng(AiLevel::Intermediate, &mut rng)),
+            Box::new(AiPlayer::new_random_with_rng(AiLevel::Advanced, &mut rng)),
+        ];
+        Engine::new(&mut game, players).run(|_, _| { });
+    }
+    let replay = Replay::new(&game);
+    let placements = replay.moves().iter().filter(|logged| match logged {
+        &Move::Placed { .. } => true,
+        _ => false,
+    }).count();
+    assert_eq!(6, placements);
+
+    let start = replay.game_at(0);
+    match replay.action_at(1) {
+        Some(&Move::Placed { player, .. }) => assert_eq!(GamePlay::PlaceNewPlane(player), start.gameplay),
+        other => panic!("the game must start with a placement, not {:?}", other),
+    }
+    assert!(start.moves.is_empty());
+
+    let third = replay.game_at(3);
+    assert_eq!(3, third.players[0].board.planes().len() + third.players[1].board.planes().len());
+
+    let end = replay.game_at(replay.len());
+    assert_eq!(game.gameplay, end.gameplay);
+    assert_eq!(game.moves, end.moves);
+    for player in 0..2 {
+        assert_eq!(format!("{}", game.players[player].scrapbook), format!("{}", end.players[player].scrapbook));
+    }
+}
+#[test]
+fn read_contenders_from_str() {
+    assert_eq!(Some(Contender::new(BombardmentStrategy::HuntAndTarget, PlacementStrategy::Random)), Contender::new_from_str("intermediate"));
+    assert_eq!(Some(Contender::new_with_level(AiLevel::Advanced)), Contender::new_from_str("advanced"));
+    let hunter = Contender::new_from_str("hunt_and_target/random").unwrap();
+    assert_eq!(Some(hunter.clone()), Contender::new_from_str(format!("{}", hunter).as_str()));
+    assert_eq!(Some(hunter), Contender::new_from_str("hunt_and_target"));
+    assert_eq!(None, Contender::new_from_str("density/clustered"));
+}
+#[test]
+fn simulation_plays_the_same_games_on_any_number_of_threads() {
+    let random = Contender::new_from_str("beginner").unwrap();
+    let hunter = Contender::new_from_str("intermediate").unwrap();
+    assert!(Simulation::new(Rules::new_even(BoardSpec::default(), 3, 2).unwrap(), 6, 42).is_none());
+    let mut simulation = Simulation::new(Rules::default(), 6, 42).unwrap();
+    simulation.threads = 1;
+    let alone = simulation.outcomes(&random, &hunter);
+    simulation.threads = 4;
+    assert_eq!(alone, simulation.outcomes(&random, &hunter));
+    assert_eq!(vec![42, 43, 44, 45, 46, 47], alone.iter().map(|outcome| outcome.seed).collect::<Vec<usize>>());
+
+    let match_up = simulation.play(&random, &hunter);
+    assert_eq!(6, match_up.games);
+    assert_eq!(6, match_up.first.wins + match_up.second.wins);
+    for statistics in &[&match_up.first, &match_up.second] {
+        assert_eq!(statistics.wins, statistics.histogram.values().sum::<usize>());
+        assert_eq!(statistics.wins == 0, statistics.mean_shots_to_win.is_none());
+    }
+}
+#[test]
+fn statistics_sum_up_the_shots_to_win() {
+    let outcome = |winner, shots| GameOutcome { seed: 0, winner: winner, shots: shots };
+    let outcomes = vec![outcome(0, 30), outcome(1, 50), outcome(0, 20), outcome(0, 30), outcome(0, 45)];
+    let statistics = Statistics::new(Contender::new_with_level(AiLevel::Advanced), 0, &outcomes);
+    assert_eq!(4, statistics.wins);
+    assert_eq!(0.8, statistics.win_rate);
+    assert_eq!(Some(31.25), statistics.mean_shots_to_win);
+    assert_eq!(Some(30.0), statistics.median_shots_to_win);
+    assert_eq!(vec![(20, 1), (30, 2), (45, 1)], statistics.histogram.into_iter().collect::<Vec<(usize, usize)>>());
+}
+#[test]
+fn placement_strategies_fill_the_fleet_on_any_map() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[5]);
+    let fleets = vec![
+        (BoardSpec::default(), Fleet::default()),
+        (BoardSpec::new(8, 8).unwrap(), Fleet::new_from_str("1 bomber, 1 classic").unwrap()),
+        (BoardSpec::new(12, 9).unwrap(), Fleet::new_from_str("1 bomber, 3 fighters").unwrap()),
+    ];
+    for name in vec!["random", "edge_hugging", "spread_out", "anti_heatmap", "clustered_decoy"] {
+        let strategy = PlacementStrategy::new(name).unwrap();
+        assert_eq!(name, format!("{}", strategy));
+        for &(spec, ref fleet) in &fleets {
+            let layout = strategy.new_layout_with_mixed_fleet(&spec, fleet.clone(), &mut rng);
+            assert!(layout.is_initialized(), "{} cannot lay out {} on {}", name, fleet, spec);
+            assert_eq!(fleet.shapes(), &layout.planes().iter().map(|plane| plane.shape().clone()).collect::<Vec<PlaneShape>>());
+        }
+    }
+}
+#[test]
+fn placement_strategies_end_on_crowded_maps() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[6]);
+    let spec = BoardSpec::new(11, 11).unwrap();
+    let fleet = Fleet::new_uniform(4, PlaneShape::bomber());
+    assert!(!fleet.fits_side_by_side(&spec));
+    assert!(Rules::new_with_fleets(spec, vec![fleet.clone(), fleet.clone()]).unwrap().can_be_laid_out());
+    for name in vec!["random", "edge_hugging", "spread_out", "anti_heatmap", "clustered_decoy"] {
+        let layout = PlacementStrategy::new(name).unwrap().new_layout_with_mixed_fleet(&spec, fleet.clone(), &mut rng);
+        assert!(layout.is_initialized(), "{} cannot lay out {} on {}", name, fleet, spec);
+    }
+}
+#[test]
+fn edge_hugging_keeps_planes_off_the_middle() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[9]);
+    let spec = BoardSpec::new(14, 14).unwrap();
+    let layout = PlacementStrategy::EdgeHugging.new_layout_with_mixed_fleet(&spec, Fleet::default(), &mut rng);
+    let middle = Coordinate::new("G7").unwrap();
+    assert!(!layout.planes().iter().any(|plane| plane.head() == &middle || plane.has_tile(&middle)));
+    for plane in layout.planes() {
+        let on_edge = |(x, y): (usize, usize)| x == 0 || y == 0 || x == 13 || y == 13;
+        let touching = plane.coordinate_iterator_on(&spec).filter_map(|tile| tile).any(|tile| on_edge(tile.as_tuple()));
+        assert!(touching, "{} does not touch the edges", plane.head());
+    }
+}
+#[test]
+fn anti_heatmap_avoids_the_tiles_shot_at_before() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[3]);
+    let spec = BoardSpec::default();
+    let mut history = ShotHistory::new(spec);
+    for row in 1..6 {
+        for column in "ABCDEFGHIJ".chars() {
+            history.record(&Coordinate::new(&format!("{}{}", column, row)).unwrap());
+        }
+    }
+    assert_eq!(50, history.total());
+    let layout = PlacementStrategy::AntiHeatmap(history.clone()).new_layout_with_mixed_fleet(&spec, Fleet::new_uniform(2, PlaneShape::fighter()), &mut rng);
+    for plane in layout.planes() {
+        assert_eq!(0, history.count(plane.head()));
+        assert!(plane.coordinate_iterator_on(&spec).filter_map(|tile| tile).all(|tile| history.count(&tile) == 0));
+    }
+}
+#[test]
+fn placement_analysis_tells_plain_layouts_from_varied_ones() {
+    let plain = PlacementAnalysis::new(&Board::from_layout_str("C3N H3N E7N").ok().unwrap());
+    assert_eq!(0.0, plain.entropy);
+    assert_eq!(AiLevel::Beginner, plain.level());
+
+    let varied = PlacementAnalysis::new(&Board::from_layout_str("D3E A8W H1N").ok().unwrap());
+    assert!(varied.entropy > 0.99);
+    assert!(varied.edge_overlap > plain.edge_overlap);
+    assert!(varied.head_exposure < plain.head_exposure);
+    assert_eq!(AiLevel::Advanced, varied.level());
+    assert_eq!(None, varied.history_entropy);
+}
+#[test]
+fn placement_analysis_weighs_layouts_against_the_common_ones() {
+    let usual = Board::from_layout_str("D3E A8W H1N").ok().unwrap();
+    let mut history = LayoutHistory::default();
+    for _ in 0..20 {
+        history.record(&usual);
+    }
+    let repeated = PlacementAnalysis::new_with_history(&usual, &history);
+    assert!(repeated.history_entropy.unwrap() < 0.1);
+    let fresh = PlacementAnalysis::new_with_history(&Board::from_layout_str("C3N H3N E7N").ok().unwrap(), &history);
+    assert!(fresh.history_entropy.unwrap() > 0.9);
+    assert!(repeated.skill() < PlacementAnalysis::new(&usual).skill());
+    assert_eq!(None, PlacementAnalysis::new_with_history(&usual, &LayoutHistory::new(BoardSpec::new(12, 12).unwrap())).history_entropy);
+}
+#[test]
+fn auto_level_settles_once_the_opponent_has_placed_its_planes() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[4]);
+    let mut game = Game::new_with_starter(2, 0, false);
+    let mut human = QueuedPlayer::new();
+    for raw_plane in vec!["C3N", "H3N", "E7N"] {
+        let (head, orientation) = raw_plane.split_at(raw_plane.len() - 1);
+        human.push_plane(Coordinate::new(head).unwrap(), Orientation::new(orientation).unwrap());
+    }
+    let mut ai = AiPlayer::new_random_with_rng(AiLevel::Auto, &mut rng);
+    {
+        let players: Vec<Box<Player>> = vec![Box::new(&mut human), Box::new(&mut ai)];
+        Engine::new(&mut game, players).run(|_, _| { });
+    }
+    assert_eq!(AiLevel::Auto, ai.level());
+    human.push_target(Coordinate::new("A1").unwrap());
+    {
+        let players: Vec<Box<Player>> = vec![Box::new(&mut human), Box::new(&mut ai)];
+        Engine::new(&mut game, players).run(|_, _| { });
+    }
+    assert_eq!(AiLevel::Beginner, ai.level());
+}
+#[test]
+fn layout_history_counts_where_the_planes_were() {
+    let board = Board::from_layout_str("C3N H3N E7N").unwrap();
+    let mut history = LayoutHistory::default();
+    history.record(&board);
+    history.record(&board);
+    assert_eq!(2, history.games());
+    assert_eq!(2, history.head_count(&Coordinate::new("C3").unwrap()));
+    assert_eq!(0, history.body_count(&Coordinate::new("C3").unwrap()));
+    assert_eq!(1.0, history.frequency(&Coordinate::new("C4").unwrap()));
+    assert_eq!(0.0, history.frequency(&Coordinate::new("J10").unwrap()));
+    let spec = BoardSpec::new_from_str("12x12").unwrap();
+    history.record(&Board::new_random_with_spec(&spec, &mut rand::thread_rng()));
+    assert_eq!(1, history.games());
+    assert_eq!(&spec, history.spec());
+}
+#[test]
+fn adaptive_strategy_hunts_where_the_planes_used_to_be() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[5]);
+    let mut history = LayoutHistory::default();
+    history.record(&Board::from_layout_str("C3N H3N E7N").unwrap());
+    let strategy = BombardmentStrategy::Adaptive(history.clone());
+    let scrapbook = Board::new();
+    for _ in 0..10 {
+        let target = strategy.next_target_with_rng(&scrapbook, &Board::new(), &mut rng).unwrap();
+        assert!(history.frequency(&target) > 0.0);
+        let target = BombardmentStrategy::next_adaptive_target_with_rng(&history, &scrapbook, &mut rng).unwrap();
+        assert!(history.frequency(&target) > 0.0);
+    }
+    assert_eq!(Some(BombardmentStrategy::Adaptive(LayoutHistory::default())), BombardmentStrategy::new(format!("{}", strategy).as_str()));
+}
+#[test]
+fn adaptive_ai_learns_from_the_stats_of_its_opponent() {
+    use rand::{SeedableRng, StdRng};
+    let mut rng = StdRng::from_seed(&[6]);
+    let mut game = Game::new_random_starter(2, false);
+    let gameplay = {
+        let players: Vec<Box<Player>> = vec![Box::new(AiPlayer::new_random()), Box::new(AiPlayer::new_random())];
+        Engine::new(&mut game, players).run(|_, _| { })
+    };
+    assert!(gameplay.is_over());
+    let mut stats = PlayerStats::new();
+    stats.record_game(&game, 0);
+    let shots = game.moves.iter().filter(|played| match played {
+        &&Move::Bombarded { player, ref result, .. } => player == 0 && *result != BombardmentResult::Retry,
+        _ => false,
+    }).count();
+    assert_eq!(1, stats.layouts.games());
+    assert_eq!(shots, stats.shots.total());
+    let ai = AiPlayer::new_random_with_stats(AiLevel::Adaptive, &Rules::default(), 1, &stats, &mut rng);
+    assert_eq!(&stats.layouts, ai.history());
+    assert_eq!(3, ai.layout().planes().len());
+}
+#[cfg(feature = "serialize")]
+#[test]
+fn player_stats_survive_a_json_round_trip() {
+    let mut stats = PlayerStats::new();
+    stats.layouts.record(&Board::from_layout_str("C3N H3N E7N").unwrap());
+    stats.shots.record(&Coordinate::new("E5").unwrap());
+    assert_eq!(stats, PlayerStats::from_json(&stats.to_json()).unwrap());
+}
+#[cfg(feature = "serialize")]
+#[test]
+fn player_stats_need_a_count_per_tile() {
+    let stats = PlayerStats::new();
+    let mut json = serde_json::to_value(&stats).unwrap();
+    json["layouts"]["heads"].as_array_mut().unwrap().pop();
+    assert!(PlayerStats::from_json(&json.to_string()).is_err());
+    let mut json = serde_json::to_value(&stats).unwrap();
+    json["shots"]["spec"] = json!({"width": 12, "height": 12});
+    assert!(PlayerStats::from_json(&json.to_string()).is_err());
+    assert!(LayoutHistory::new_from_counts(BoardSpec::new(8, 8).unwrap(), 0, vec![0; 64], vec![0; 64]).is_some());
+    assert_eq!(None, ShotHistory::new_from_counts(BoardSpec { width: 30, height: 30 }, vec![0; 900]));
+}